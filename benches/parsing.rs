@@ -1,6 +1,6 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use rand_core::{OsRng, RngCore};
-use requiem::{Gate, LogicTree, TerminalId};
+use requiem::{ArenaTree, Gate, LogicTree, TerminalId};
 
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -16,6 +16,8 @@ fn gate_from_u32(num: u32) -> Gate {
     }
 }
 
+// `u32::is_multiple_of` would raise the minimum supported Rust to 1.87
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
 fn generate_expression(rng: &mut OsRng, size: usize) -> String {
     let mut expression = String::from("0");
     let mut parentheses = 0;
@@ -58,6 +60,18 @@ fn bench_parsing(c: &mut Criterion) {
     group.bench_function("bench_1000", |b| {
         b.iter(|| LogicTree::from_str(&expression_1000).unwrap())
     });
+
+    group.bench_function("arena_10", |b| {
+        b.iter(|| ArenaTree::from_str(&expression_10).unwrap())
+    });
+
+    group.bench_function("arena_100", |b| {
+        b.iter(|| ArenaTree::from_str(&expression_100).unwrap())
+    });
+
+    group.bench_function("arena_1000", |b| {
+        b.iter(|| ArenaTree::from_str(&expression_1000).unwrap())
+    });
 }
 
 fn bench_tree_evaluation(c: &mut Criterion) {
@@ -90,6 +104,20 @@ fn bench_tree_evaluation(c: &mut Criterion) {
     group.bench_function("bench_1000", |b| {
         b.iter(|| tree_1000.evaluate(&map_1000).unwrap());
     });
+
+    let arena_10 = ArenaTree::from_tree(&tree_10);
+    let arena_100 = ArenaTree::from_tree(&tree_100);
+    let arena_1000 = ArenaTree::from_tree(&tree_1000);
+
+    group.bench_function("arena_evaluate_10", |b| {
+        b.iter(|| arena_10.evaluate(&map_10).unwrap());
+    });
+    group.bench_function("arena_evaluate_100", |b| {
+        b.iter(|| arena_100.evaluate(&map_100).unwrap());
+    });
+    group.bench_function("arena_evaluate_1000", |b| {
+        b.iter(|| arena_1000.evaluate(&map_1000).unwrap());
+    });
 }
 
 criterion_group!(benches, bench_parsing, bench_tree_evaluation,);
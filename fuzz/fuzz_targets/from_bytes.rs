@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use requiem::LogicTree;
+
+fuzz_target!(|bytes: &[u8]| {
+    // anything that decodes must encode back to the same bytes
+    if let Ok(tree) = LogicTree::from_bytes(bytes) {
+        assert_eq!(tree.to_bytes(), bytes);
+    }
+});
@@ -0,0 +1,475 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+use thiserror::Error;
+
+use std::collections::HashMap;
+
+#[derive(Error, Debug)]
+pub enum BddError {
+    #[error("the variable order is empty")]
+    EmptyOrder,
+    #[error("terminal {0} is missing from the variable order")]
+    MissingVariable(TerminalId),
+    #[error("terminal {0} appears more than once in the variable order")]
+    DuplicateVariable(TerminalId),
+}
+
+#[derive(Error, Debug)]
+pub enum ProbabilityError {
+    #[error("no probability given for terminal {0}")]
+    MissingProbability(TerminalId),
+    #[error("probability {probability} of terminal {terminal} is outside of [0, 1]")]
+    InvalidProbability {
+        terminal: TerminalId,
+        probability: f64,
+    },
+}
+
+/// Strategies for ordering the variables of a [`Bdd`].
+///
+/// The size of a BDD heavily depends on its variable order, related terminals
+/// should be close to each other.
+#[derive(Clone, Debug)]
+pub enum VariableOrder {
+    /// Terminals ordered by their id.
+    Ascending,
+    /// Terminals ordered by their first occurrence in the expression.
+    FirstOccurrence,
+    /// An explicit order which must contain every referenced terminal.
+    Custom(Vec<TerminalId>),
+}
+
+/// Handle of a node within a [`Bdd`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BddNode(u32);
+
+impl BddNode {
+    pub const FALSE: Self = Self(0);
+    pub const TRUE: Self = Self(1);
+
+    pub fn constant(value: bool) -> Self {
+        if value {
+            Self::TRUE
+        } else {
+            Self::FALSE
+        }
+    }
+
+    /// Returns the value of the node if it is one of the two leaves.
+    pub fn as_constant(self) -> Option<bool> {
+        match self {
+            Self::FALSE => Some(false),
+            Self::TRUE => Some(true),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    level: usize,
+    low: BddNode,
+    high: BddNode,
+}
+
+/// Reduced ordered binary decision diagram.
+///
+/// Nodes are hash-consed through a unique table, so two nodes of the same
+/// diagram represent equivalent functions exactly if their handles are equal.
+/// The diagram holds a root, but [`Bdd::apply`] can combine any of its nodes.
+#[derive(Clone, Debug)]
+pub struct Bdd {
+    order: Vec<TerminalId>,
+    levels: HashMap<TerminalId, usize>,
+    nodes: Vec<Node>,
+    unique: HashMap<(usize, BddNode, BddNode), BddNode>,
+    computed: HashMap<(Gate, BddNode, BddNode), BddNode>,
+    root: BddNode,
+}
+
+impl Bdd {
+    /// Creates a diagram over the given variables, with its root set to
+    /// `false`.
+    pub fn new(order: Vec<TerminalId>) -> Result<Self, BddError> {
+        if order.is_empty() {
+            return Err(BddError::EmptyOrder);
+        }
+        let mut levels = HashMap::with_capacity(order.len());
+        for (level, id) in order.iter().enumerate() {
+            if levels.insert(*id, level).is_some() {
+                return Err(BddError::DuplicateVariable(*id));
+            }
+        }
+        // leaves sit below every variable
+        let leaf = |value| Node {
+            level: order.len(),
+            low: BddNode::constant(value),
+            high: BddNode::constant(value),
+        };
+        Ok(Self {
+            nodes: vec![leaf(false), leaf(true)],
+            order,
+            levels,
+            unique: HashMap::new(),
+            computed: HashMap::new(),
+            root: BddNode::FALSE,
+        })
+    }
+
+    /// Builds the diagram of the tree with terminals in ascending order.
+    pub fn from_tree(tree: &LogicTree) -> Self {
+        Self::from_tree_with_order(tree, VariableOrder::Ascending)
+            .expect("every terminal is ordered")
+    }
+
+    pub fn from_tree_with_order(tree: &LogicTree, order: VariableOrder) -> Result<Self, BddError> {
+        let order = match order {
+            VariableOrder::Ascending => tree.terminals().into_iter().collect(),
+            VariableOrder::FirstOccurrence => {
+                let mut order = Vec::new();
+                for node in tree.preorder() {
+                    if let LogicTree::Terminal(id) = node {
+                        if !order.contains(id) {
+                            order.push(*id);
+                        }
+                    }
+                }
+                order
+            }
+            VariableOrder::Custom(order) => order,
+        };
+        let mut bdd = Self::new(order)?;
+        bdd.root = bdd.build(tree)?;
+        Ok(bdd)
+    }
+
+    pub fn order(&self) -> &[TerminalId] {
+        &self.order
+    }
+
+    pub fn root(&self) -> BddNode {
+        self.root
+    }
+
+    pub fn set_root(&mut self, root: BddNode) {
+        self.root = root;
+    }
+
+    /// Number of nodes reachable from the root, including the leaves.
+    pub fn node_count(&self) -> usize {
+        let mut visited = vec![false; self.nodes.len()];
+        let mut stack = vec![self.root];
+        let mut count = 0;
+        while let Some(node) = stack.pop() {
+            if std::mem::replace(&mut visited[node.0 as usize], true) {
+                continue;
+            }
+            count += 1;
+            if node.as_constant().is_none() {
+                let Node { low, high, .. } = self.nodes[node.0 as usize];
+                stack.push(low);
+                stack.push(high);
+            }
+        }
+        count
+    }
+
+    /// Returns the node testing a single variable.
+    pub fn variable(&mut self, id: TerminalId) -> Result<BddNode, BddError> {
+        let level = *self.levels.get(&id).ok_or(BddError::MissingVariable(id))?;
+        Ok(self.make(level, BddNode::FALSE, BddNode::TRUE))
+    }
+
+    /// Builds the diagram of a tree without changing the root.
+    pub fn build(&mut self, tree: &LogicTree) -> Result<BddNode, BddError> {
+        match tree {
+            LogicTree::Terminal(id) => self.variable(*id),
+            LogicTree::Gate { gate, left, right } => {
+                let left = self.build(left)?;
+                let right = self.build(right)?;
+                Ok(self.apply(*gate, left, right))
+            }
+        }
+    }
+
+    /// Combines two nodes with a gate.
+    pub fn apply(&mut self, gate: Gate, left: BddNode, right: BddNode) -> BddNode {
+        if let (Some(a), Some(b)) = (left.as_constant(), right.as_constant()) {
+            return BddNode::constant(gate.apply(a, b));
+        }
+        match (gate, left.as_constant(), right.as_constant()) {
+            (Gate::And, Some(false), _) | (Gate::And, _, Some(false)) => return BddNode::FALSE,
+            (Gate::Or, Some(true), _) | (Gate::Or, _, Some(true)) => return BddNode::TRUE,
+            (Gate::And, Some(true), _) | (Gate::Or, Some(false), _) => return right,
+            (Gate::And, _, Some(true)) | (Gate::Or, _, Some(false)) => return left,
+            (Gate::And, _, _) | (Gate::Or, _, _) if left == right => return left,
+            (Gate::Xor, _, _) if left == right => return BddNode::FALSE,
+            _ => {}
+        }
+        if let Some(result) = self.computed.get(&(gate, left, right)) {
+            return *result;
+        }
+
+        let a = self.nodes[left.0 as usize];
+        let b = self.nodes[right.0 as usize];
+        let level = a.level.min(b.level);
+        let (a_low, a_high) = self.cofactors(left, level);
+        let (b_low, b_high) = self.cofactors(right, level);
+        let low = self.apply(gate, a_low, b_low);
+        let high = self.apply(gate, a_high, b_high);
+        let result = self.make(level, low, high);
+        self.computed.insert((gate, left, right), result);
+        result
+    }
+
+    /// Follows the root for the given terminal values. Only terminals tested
+    /// on the path have to be present.
+    pub fn evaluate(&self, terminals: &HashMap<TerminalId, bool>) -> Result<bool, String> {
+        self.evaluate_node(self.root, terminals)
+    }
+
+    pub fn evaluate_node(
+        &self,
+        mut node: BddNode,
+        terminals: &HashMap<TerminalId, bool>,
+    ) -> Result<bool, String> {
+        loop {
+            if let Some(value) = node.as_constant() {
+                return Ok(value);
+            }
+            let Node { level, low, high } = self.nodes[node.0 as usize];
+            let value = terminals
+                .get(&self.order[level])
+                .ok_or_else(|| "Invalid terminals map".to_string())?;
+            node = if *value { high } else { low };
+        }
+    }
+
+    /// Counts the assignments of every ordered variable under which the root
+    /// evaluates to `true`. Returns `None` if the count overflows.
+    pub fn model_count(&self) -> Option<u128> {
+        let mut counts = HashMap::new();
+        let count = self.count_below(self.root, &mut counts)?;
+        count.checked_mul(pow2(self.level(self.root))?)
+    }
+
+    /// Probability of the root evaluating to `true` if every variable is
+    /// `true` independently with the given probability.
+    ///
+    /// Only variables the root depends on need a probability.
+    pub fn probability(
+        &self,
+        probabilities: &HashMap<TerminalId, f64>,
+    ) -> Result<f64, ProbabilityError> {
+        let mut computed = HashMap::new();
+        self.probability_below(self.root, probabilities, &mut computed)
+    }
+
+    /// Converts the root back into a tree by Shannon expansion.
+    pub fn to_tree(&self) -> LogicTree {
+        self.node_to_tree(self.root)
+    }
+
+    pub fn node_to_tree(&self, node: BddNode) -> LogicTree {
+        if let Some(value) = node.as_constant() {
+            return LogicTree::constant(value, self.order[0]);
+        }
+        let Node { level, low, high } = self.nodes[node.0 as usize];
+        let variable = LogicTree::terminal(self.order[level]);
+        match (low.as_constant(), high.as_constant()) {
+            (Some(false), Some(true)) => variable,
+            (Some(true), Some(false)) => variable.not(),
+            (Some(false), _) => variable.and(self.node_to_tree(high)),
+            (Some(true), _) => variable.not().or(self.node_to_tree(high)),
+            (_, Some(false)) => self.node_to_tree(low).and(variable.not()),
+            (_, Some(true)) => variable.or(self.node_to_tree(low)),
+            _ => variable
+                .clone()
+                .and(self.node_to_tree(high))
+                .or(variable.not().and(self.node_to_tree(low))),
+        }
+    }
+
+    /// Returns the tested variable and the low and high children of an inner
+    /// node.
+    pub(crate) fn branch(&self, node: BddNode) -> Option<(TerminalId, BddNode, BddNode)> {
+        node.as_constant().is_none().then(|| {
+            let Node { level, low, high } = self.nodes[node.0 as usize];
+            (self.order[level], low, high)
+        })
+    }
+
+    fn level(&self, node: BddNode) -> usize {
+        self.nodes[node.0 as usize].level
+    }
+
+    /// Number of satisfying assignments of the variables from the level of
+    /// the node downwards.
+    fn count_below(&self, node: BddNode, counts: &mut HashMap<BddNode, u128>) -> Option<u128> {
+        if let Some(value) = node.as_constant() {
+            return Some(value as u128);
+        }
+        if let Some(count) = counts.get(&node) {
+            return Some(*count);
+        }
+        let Node { level, low, high } = self.nodes[node.0 as usize];
+        // variables skipped by an edge may take any value
+        let low_count = self
+            .count_below(low, counts)?
+            .checked_mul(pow2(self.level(low) - level - 1)?)?;
+        let high_count = self
+            .count_below(high, counts)?
+            .checked_mul(pow2(self.level(high) - level - 1)?)?;
+        let count = low_count.checked_add(high_count)?;
+        counts.insert(node, count);
+        Some(count)
+    }
+
+    fn probability_below(
+        &self,
+        node: BddNode,
+        probabilities: &HashMap<TerminalId, f64>,
+        computed: &mut HashMap<BddNode, f64>,
+    ) -> Result<f64, ProbabilityError> {
+        if let Some(value) = node.as_constant() {
+            return Ok(if value { 1.0 } else { 0.0 });
+        }
+        if let Some(probability) = computed.get(&node) {
+            return Ok(*probability);
+        }
+        let Node { level, low, high } = self.nodes[node.0 as usize];
+        let terminal = self.order[level];
+        let p = *probabilities
+            .get(&terminal)
+            .ok_or(ProbabilityError::MissingProbability(terminal))?;
+        if !(0.0..=1.0).contains(&p) {
+            return Err(ProbabilityError::InvalidProbability {
+                terminal,
+                probability: p,
+            });
+        }
+        let probability = p * self.probability_below(high, probabilities, computed)?
+            + (1.0 - p) * self.probability_below(low, probabilities, computed)?;
+        computed.insert(node, probability);
+        Ok(probability)
+    }
+
+    fn cofactors(&self, node: BddNode, level: usize) -> (BddNode, BddNode) {
+        let Node {
+            level: node_level,
+            low,
+            high,
+        } = self.nodes[node.0 as usize];
+        if node_level == level {
+            (low, high)
+        } else {
+            (node, node)
+        }
+    }
+
+    fn make(&mut self, level: usize, low: BddNode, high: BddNode) -> BddNode {
+        if low == high {
+            return low;
+        }
+        if let Some(node) = self.unique.get(&(level, low, high)) {
+            return *node;
+        }
+        let node = BddNode(self.nodes.len() as u32);
+        self.nodes.push(Node { level, low, high });
+        self.unique.insert((level, low, high), node);
+        node
+    }
+}
+
+fn pow2(exponent: usize) -> Option<u128> {
+    1_u128.checked_shl(u32::try_from(exponent).ok()?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::str::FromStr;
+
+    #[test]
+    fn evaluation() {
+        for seed in 0..200 {
+            let tree = random_tree(seed, 5, 12);
+            let bdd = Bdd::from_tree(&tree);
+            for assignment in assignments(&tree.terminals()) {
+                assert_eq!(
+                    bdd.evaluate(&assignment).unwrap(),
+                    tree.evaluate(&assignment).unwrap(),
+                    "{}",
+                    tree
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn canonical() {
+        let tree = LogicTree::from_str("0 NAND 1").unwrap();
+        let mut bdd = Bdd::from_tree(&tree);
+        let other = LogicTree::from_str("(0 NAND 0) OR (1 NAND 1)").unwrap();
+        assert_eq!(bdd.build(&other).unwrap(), bdd.root());
+
+        let tautology = LogicTree::from_str("0 OR (0 NAND 0)").unwrap();
+        assert_eq!(Bdd::from_tree(&tautology).root(), BddNode::TRUE);
+        let contradiction = LogicTree::from_str("0 AND 1 AND (1 XOR 1)").unwrap();
+        let bdd = Bdd::from_tree(&contradiction);
+        assert_eq!(bdd.root(), BddNode::FALSE);
+        assert_eq!(bdd.node_count(), 1);
+        assert!(!bdd.evaluate(&HashMap::new()).unwrap());
+    }
+
+    #[test]
+    fn apply() {
+        let mut bdd = Bdd::new(vec![0, 1]).unwrap();
+        let a = bdd.variable(0).unwrap();
+        let b = bdd.variable(1).unwrap();
+        for gate in [Gate::And, Gate::Or, Gate::Nand, Gate::Nor, Gate::Xor] {
+            let node = bdd.apply(gate, a, b);
+            for (x, y) in [(false, false), (false, true), (true, false), (true, true)] {
+                let terminals = [(0, x), (1, y)].into_iter().collect();
+                assert_eq!(
+                    bdd.evaluate_node(node, &terminals).unwrap(),
+                    gate.apply(x, y)
+                );
+            }
+        }
+        assert!(bdd.variable(2).is_err());
+    }
+
+    #[test]
+    fn variable_order() {
+        let tree = LogicTree::from_str("(0 AND 3) OR (1 AND 4) OR (2 AND 5)").unwrap();
+        let ascending = Bdd::from_tree(&tree);
+        let paired = Bdd::from_tree_with_order(&tree, VariableOrder::FirstOccurrence).unwrap();
+        assert_eq!(paired.order(), [0, 3, 1, 4, 2, 5]);
+        assert!(paired.node_count() < ascending.node_count());
+        assert_eq!(paired.node_count(), 8);
+
+        let custom = VariableOrder::Custom(vec![5, 4, 3, 2, 1]);
+        match Bdd::from_tree_with_order(&tree, custom) {
+            Err(BddError::MissingVariable(0)) => {}
+            _ => panic!("terminal 0 should be missing"),
+        }
+        let duplicate = VariableOrder::Custom(vec![0, 1, 0]);
+        assert!(Bdd::from_tree_with_order(&tree, duplicate).is_err());
+        assert!(Bdd::new(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn back_to_tree() {
+        for seed in 0..200 {
+            let tree = random_tree(seed, 4, 10);
+            let bdd = Bdd::from_tree(&tree);
+            let rebuilt = bdd.to_tree();
+            assert!(rebuilt.equivalent(&tree).holds(), "{} => {}", tree, rebuilt);
+        }
+        let tree = LogicTree::from_str("0 AND (0 OR 1)").unwrap();
+        assert_eq!(Bdd::from_tree(&tree).to_tree().to_string(), "0");
+    }
+}
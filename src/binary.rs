@@ -0,0 +1,292 @@
+//! Compact versioned binary representation of a [`LogicTree`].
+//!
+//! An encoding starts with the [`BINARY_FORMAT_VERSION`] byte, followed by
+//! the nodes in preorder. Each node starts with an opcode byte whose low three
+//! bits select the node kind:
+//!
+//! | bits  | node                |
+//! |-------|---------------------|
+//! | `0`   | terminal            |
+//! | `1-5` | `AND`, `OR`, `NAND`, `NOR`, `XOR` |
+//!
+//! The high five bits of a gate opcode are zero. For a terminal they hold the
+//! id itself when it is below 31, otherwise they are all set and the id minus
+//! 31 follows as an unsigned LEB128 varint.
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+use thiserror::Error;
+
+pub const BINARY_FORMAT_VERSION: u8 = 1;
+
+/// Deepest tree accepted by [`LogicTree::from_bytes`].
+pub const MAX_BINARY_DEPTH: usize = 4096;
+
+const TERMINAL: u8 = 0;
+const INLINE_TERMINALS: u8 = 31;
+
+#[derive(Error, Debug)]
+pub enum BinaryError {
+    #[error("unexpected end of input")]
+    Truncated,
+    #[error("unsupported format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("invalid opcode {opcode:#04x} at byte {offset}")]
+    InvalidOpcode { offset: usize, opcode: u8 },
+    #[error("invalid terminal id at byte {0}")]
+    InvalidTerminal(usize),
+    #[error("tree is deeper than {MAX_BINARY_DEPTH}")]
+    TooDeep,
+    #[error("trailing bytes after the tree at byte {0}")]
+    TrailingBytes(usize),
+}
+
+pub(crate) fn opcode(gate: Gate) -> u8 {
+    match gate {
+        Gate::And => 1,
+        Gate::Or => 2,
+        Gate::Nand => 3,
+        Gate::Nor => 4,
+        Gate::Xor => 5,
+    }
+}
+
+fn encode(tree: &LogicTree, bytes: &mut Vec<u8>) {
+    match tree {
+        LogicTree::Terminal(id) => {
+            if *id < TerminalId::from(INLINE_TERMINALS) {
+                bytes.push(TERMINAL | (*id as u8) << 3);
+            } else {
+                bytes.push(TERMINAL | INLINE_TERMINALS << 3);
+                let mut rest = id - TerminalId::from(INLINE_TERMINALS);
+                while rest >= 0x80 {
+                    bytes.push(rest as u8 | 0x80);
+                    rest >>= 7;
+                }
+                bytes.push(rest as u8);
+            }
+        }
+        LogicTree::Gate { gate, left, right } => {
+            bytes.push(opcode(*gate));
+            encode(left, bytes);
+            encode(right, bytes);
+        }
+    }
+}
+
+enum Node {
+    Terminal(TerminalId),
+    Gate(Gate),
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Decoder<'_> {
+    fn next_byte(&mut self) -> Result<u8, BinaryError> {
+        let byte = *self.bytes.get(self.offset).ok_or(BinaryError::Truncated)?;
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn next_node(&mut self) -> Result<Node, BinaryError> {
+        let offset = self.offset;
+        let opcode = self.next_byte()?;
+        let node = match (opcode & 0x07, opcode >> 3) {
+            (TERMINAL, INLINE_TERMINALS) => {
+                let mut rest: TerminalId = 0;
+                for shift in (0..TerminalId::BITS).step_by(7) {
+                    let byte = self.next_byte()?;
+                    let bits = TerminalId::from(byte & 0x7f);
+                    // the last byte may only carry the remaining high bits
+                    if bits << shift >> shift != bits {
+                        return Err(BinaryError::InvalidTerminal(offset));
+                    }
+                    rest |= bits << shift;
+                    if byte & 0x80 == 0 {
+                        // a canonical varint has no trailing zero bytes
+                        if byte == 0 && shift > 0 {
+                            return Err(BinaryError::InvalidTerminal(offset));
+                        }
+                        let id = rest
+                            .checked_add(TerminalId::from(INLINE_TERMINALS))
+                            .ok_or(BinaryError::InvalidTerminal(offset))?;
+                        return Ok(Node::Terminal(id));
+                    }
+                }
+                return Err(BinaryError::InvalidTerminal(offset));
+            }
+            (TERMINAL, id) => Node::Terminal(TerminalId::from(id)),
+            (1, 0) => Node::Gate(Gate::And),
+            (2, 0) => Node::Gate(Gate::Or),
+            (3, 0) => Node::Gate(Gate::Nand),
+            (4, 0) => Node::Gate(Gate::Nor),
+            (5, 0) => Node::Gate(Gate::Xor),
+            _ => return Err(BinaryError::InvalidOpcode { offset, opcode }),
+        };
+        Ok(node)
+    }
+}
+
+impl LogicTree {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![BINARY_FORMAT_VERSION];
+        encode(self, &mut bytes);
+        bytes
+    }
+
+    /// Decodes a tree written by [`LogicTree::to_bytes`].
+    ///
+    /// Decoding does not recurse, and rejects trees deeper than
+    /// [`MAX_BINARY_DEPTH`] so that untrusted input cannot build trees which
+    /// overflow the stack of the recursive methods.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BinaryError> {
+        let (&version, _) = bytes.split_first().ok_or(BinaryError::Truncated)?;
+        if version != BINARY_FORMAT_VERSION {
+            return Err(BinaryError::UnsupportedVersion(version));
+        }
+        let mut decoder = Decoder { bytes, offset: 1 };
+        // gates waiting for their operands, with the left one once decoded
+        let mut pending: Vec<(Gate, Option<Self>)> = Vec::new();
+        loop {
+            let mut tree = match decoder.next_node()? {
+                Node::Gate(gate) => {
+                    if pending.len() >= MAX_BINARY_DEPTH {
+                        return Err(BinaryError::TooDeep);
+                    }
+                    pending.push((gate, None));
+                    continue;
+                }
+                Node::Terminal(id) => Self::Terminal(id),
+            };
+            loop {
+                match pending.pop() {
+                    None => {
+                        return if decoder.offset == bytes.len() {
+                            Ok(tree)
+                        } else {
+                            Err(BinaryError::TrailingBytes(decoder.offset))
+                        };
+                    }
+                    Some((gate, None)) => {
+                        pending.push((gate, Some(tree)));
+                        break;
+                    }
+                    Some((gate, Some(left))) => tree = Self::gate(gate, left, tree),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::random_tree;
+    use std::str::FromStr;
+
+    fn is_equal_discriminant(this: &BinaryError, that: &BinaryError) -> bool {
+        std::mem::discriminant(this) == std::mem::discriminant(that)
+    }
+
+    #[test]
+    fn encoding() {
+        let tree = LogicTree::from_str("0 NOR 30 XOR 31").unwrap();
+        assert_eq!(tree.to_bytes(), [1, 5, 4, 0, 30 << 3, 31 << 3, 0]);
+
+        let tree = LogicTree::Terminal(31 + 300);
+        assert_eq!(tree.to_bytes(), [1, 31 << 3, 0xac, 0x02]);
+
+        for id in [0, 30, 31, 158, 159, 1 << 20, TerminalId::MAX] {
+            let tree = LogicTree::Terminal(id);
+            match LogicTree::from_bytes(&tree.to_bytes()).unwrap() {
+                LogicTree::Terminal(decoded) => assert_eq!(decoded, id),
+                _ => panic!("should be terminal"),
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for seed in 0..100 {
+            let tree = random_tree(seed, 200, 20);
+            let decoded = LogicTree::from_bytes(&tree.to_bytes()).unwrap();
+            assert_eq!(decoded.to_string(), tree.to_string());
+        }
+
+        let source = (1..MAX_BINARY_DEPTH + 1)
+            .fold("0".to_string(), |source, i| format!("{} AND {}", source, i));
+        let deepest = LogicTree::from_str(&source).unwrap();
+        assert!(LogicTree::from_bytes(&deepest.to_bytes()).is_ok());
+    }
+
+    #[test]
+    fn invalid_encodings() {
+        let mut too_deep = vec![1; MAX_BINARY_DEPTH + 2];
+        too_deep[0] = BINARY_FORMAT_VERSION;
+        let cases: [(&[u8], BinaryError); 11] = [
+            (&[], BinaryError::Truncated),
+            (&[1], BinaryError::Truncated),
+            (&[1, 1, 0], BinaryError::Truncated),
+            (&[1, 31 << 3, 0x80], BinaryError::Truncated),
+            (&[2, 0], BinaryError::UnsupportedVersion(2)),
+            (
+                &[1, 6, 0, 0],
+                BinaryError::InvalidOpcode {
+                    offset: 0,
+                    opcode: 0,
+                },
+            ),
+            (
+                &[1, 1 | 8, 0, 0],
+                BinaryError::InvalidOpcode {
+                    offset: 0,
+                    opcode: 0,
+                },
+            ),
+            (
+                &[1, 31 << 3, 0xff, 0xff, 0xff, 0xff, 0x0f],
+                BinaryError::InvalidTerminal(0),
+            ),
+            (&[1, 31 << 3, 0x80, 0x00], BinaryError::InvalidTerminal(0)),
+            (&too_deep, BinaryError::TooDeep),
+            (&[1, 0, 0], BinaryError::TrailingBytes(0)),
+        ];
+        for (bytes, error) in cases {
+            assert!(
+                is_equal_discriminant(&LogicTree::from_bytes(bytes).unwrap_err(), &error),
+                "{:?}",
+                bytes
+            );
+        }
+        // the largest id fits, anything above overflows
+        let largest = LogicTree::Terminal(TerminalId::MAX).to_bytes();
+        assert_eq!(largest, [1, 31 << 3, 0xe0, 0xff, 0xff, 0xff, 0x0f]);
+        let mut overflow = largest.clone();
+        overflow[2] += 1;
+        assert!(LogicTree::from_bytes(&overflow).is_err());
+    }
+
+    #[test]
+    fn arbitrary_bytes() {
+        // every prefix and single byte corruption of valid encodings either
+        // decodes or errors
+        for seed in 0..20 {
+            let bytes = random_tree(seed, 100, 10).to_bytes();
+            for end in 0..bytes.len() {
+                assert!(LogicTree::from_bytes(&bytes[..end]).is_err());
+            }
+            for i in 1..bytes.len() {
+                for byte in 0..=u8::MAX {
+                    let mut corrupted = bytes.clone();
+                    corrupted[i] = byte;
+                    if let Ok(tree) = LogicTree::from_bytes(&corrupted) {
+                        assert_eq!(tree.to_bytes(), corrupted);
+                    }
+                }
+            }
+        }
+    }
+}
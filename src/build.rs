@@ -0,0 +1,175 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+impl LogicTree {
+    pub fn terminal(id: TerminalId) -> Self {
+        Self::Terminal(id)
+    }
+
+    pub fn gate(gate: Gate, left: Self, right: Self) -> Self {
+        Self::Gate {
+            gate,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn and(self, other: Self) -> Self {
+        Self::gate(Gate::And, self, other)
+    }
+
+    pub fn or(self, other: Self) -> Self {
+        Self::gate(Gate::Or, self, other)
+    }
+
+    pub fn nand(self, other: Self) -> Self {
+        Self::gate(Gate::Nand, self, other)
+    }
+
+    pub fn nor(self, other: Self) -> Self {
+        Self::gate(Gate::Nor, self, other)
+    }
+
+    pub fn xor(self, other: Self) -> Self {
+        Self::gate(Gate::Xor, self, other)
+    }
+
+    /// Tree that evaluates to `value` under every assignment.
+    ///
+    /// There are no constants in the expression syntax, so they are expressed
+    /// through the `witness` terminal as `x OR (x NAND x)` and `x XOR x`.
+    pub fn constant(value: bool, witness: TerminalId) -> Self {
+        let terminal = Self::terminal(witness);
+        if value {
+            terminal.clone().or(terminal.not())
+        } else {
+            terminal.clone().xor(terminal)
+        }
+    }
+
+    /// Negates the tree.
+    ///
+    /// There is no unary gate, so a terminal is negated as `x NAND x`. Gates
+    /// are flipped to their dual instead, and `a XOR b` becomes `a XOR NOT b`,
+    /// so the tree grows by at most two nodes however often it is negated.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        match self {
+            Self::Terminal(id) => Self::terminal(id).nand(Self::terminal(id)),
+            Self::Gate { gate, left, right } => match gate {
+                Gate::And => (*left).nand(*right),
+                Gate::Or => (*left).nor(*right),
+                Gate::Nand => (*left).and(*right),
+                Gate::Nor => (*left).or(*right),
+                Gate::Xor => (*left).xor(right.not()),
+            },
+        }
+    }
+}
+
+impl From<TerminalId> for LogicTree {
+    fn from(id: TerminalId) -> Self {
+        Self::terminal(id)
+    }
+}
+
+impl BitAnd for LogicTree {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        self.and(rhs)
+    }
+}
+
+impl BitOr for LogicTree {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        self.or(rhs)
+    }
+}
+
+impl BitXor for LogicTree {
+    type Output = Self;
+    fn bitxor(self, rhs: Self) -> Self {
+        self.xor(rhs)
+    }
+}
+
+impl Not for LogicTree {
+    type Output = Self;
+    fn not(self) -> Self {
+        LogicTree::not(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    #[test]
+    fn build_with_methods() {
+        let t = LogicTree::terminal;
+        let tree = t(0).and(t(1)).or(t(0).nand(t(2)).or(t(3)));
+        assert_eq!(tree.to_string(), "0 AND 1 OR (0 NAND 2 OR 3)");
+        let tree = t(4).nor(t(5)).xor(t(6).not());
+        assert_eq!(tree.to_string(), "4 NOR 5 XOR (6 NAND 6)");
+        assert_eq!(LogicTree::from(9).to_string(), "9");
+    }
+
+    #[test]
+    fn constants() {
+        let always = LogicTree::constant(true, 3);
+        let never = LogicTree::constant(false, 3);
+        for value in [false, true] {
+            let terminals = [(3, value)].into_iter().collect();
+            assert!(always.evaluate(&terminals).unwrap());
+            assert!(!never.evaluate(&terminals).unwrap());
+        }
+    }
+
+    #[test]
+    fn build_with_operators() {
+        let t = LogicTree::terminal;
+        let tree = t(0) & (t(1) | t(2));
+        assert_eq!(tree.to_string(), "0 AND (1 OR 2)");
+        let tree = !(t(0) ^ t(1)) & t(2);
+        assert_eq!(tree.to_string(), "0 XOR (1 NAND 1) AND 2");
+
+        let parsed = LogicTree::from_str(&tree.to_string()).unwrap();
+        for bits in 0..8_u32 {
+            let terminals = (0..3)
+                .map(|i| (i, bits & (1 << i) != 0))
+                .collect::<HashMap<TerminalId, bool>>();
+            let expected = !(terminals[&0] ^ terminals[&1]) && terminals[&2];
+            assert_eq!(tree.evaluate(&terminals).unwrap(), expected);
+            assert_eq!(parsed.evaluate(&terminals).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn nested_negation() {
+        let t = LogicTree::terminal;
+        for tree in [t(0), t(0) & t(1), t(0) | t(1) ^ t(2), t(0) ^ (t(1) ^ t(2))] {
+            let size = tree.node_count();
+            let mut negated = tree.clone();
+            for i in 0..64 {
+                negated = !negated;
+                assert!(negated.node_count() <= size + 2);
+                for bits in 0..8_u32 {
+                    let terminals =
+                        (0..3)
+                            .map(|i| (i, bits & (1 << i) != 0))
+                            .collect::<HashMap<TerminalId, bool>>();
+                    assert_eq!(
+                        negated.evaluate(&terminals).unwrap(),
+                        tree.evaluate(&terminals).unwrap() == (i % 2 == 1)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::binary::opcode;
+use crate::gate::Gate;
+use crate::token::LogicTree;
+
+use std::cmp::Ordering;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn is_associative(gate: Gate) -> bool {
+    matches!(gate, Gate::And | Gate::Or | Gate::Xor)
+}
+
+// terminals by id before gates, gates by their binary opcode and then their
+// children, so the order does not depend on the declaration order of `Gate`
+fn compare(this: &LogicTree, that: &LogicTree) -> Ordering {
+    match (this, that) {
+        (LogicTree::Terminal(a), LogicTree::Terminal(b)) => a.cmp(b),
+        (LogicTree::Terminal(_), LogicTree::Gate { .. }) => Ordering::Less,
+        (LogicTree::Gate { .. }, LogicTree::Terminal(_)) => Ordering::Greater,
+        (
+            LogicTree::Gate {
+                gate: a,
+                left: a_left,
+                right: a_right,
+            },
+            LogicTree::Gate {
+                gate: b,
+                left: b_left,
+                right: b_right,
+            },
+        ) => opcode(*a)
+            .cmp(&opcode(*b))
+            .then_with(|| compare(a_left, b_left))
+            .then_with(|| compare(a_right, b_right)),
+    }
+}
+
+fn collect_operands(tree: &LogicTree, chain: Gate, operands: &mut Vec<LogicTree>) {
+    match tree {
+        LogicTree::Gate { gate, left, right } if *gate == chain => {
+            collect_operands(left, chain, operands);
+            collect_operands(right, chain, operands);
+        }
+        _ => operands.push(tree.canonicalize()),
+    }
+}
+
+fn hash_into(tree: &LogicTree, hash: &mut u64) {
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            *hash ^= u64::from(*byte);
+            *hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    };
+    match tree {
+        LogicTree::Terminal(id) => {
+            write(&[0]);
+            write(&id.to_le_bytes());
+        }
+        LogicTree::Gate { gate, left, right } => {
+            write(&[opcode(*gate)]);
+            hash_into(left, hash);
+            hash_into(right, hash);
+        }
+    }
+}
+
+impl LogicTree {
+    /// Rewrites the tree into a canonical form, such that trees which only
+    /// differ in the order of gate inputs or in the grouping of `AND`, `OR`
+    /// and `XOR` chains become equal.
+    ///
+    /// Every gate is commutative, so the inputs of `NAND` and `NOR` are
+    /// sorted. Chains of the same associative gate are flattened, their
+    /// operands sorted and rebuilt left to right, e.g. `2 AND (1 AND 0)`
+    /// becomes `0 AND 1 AND 2`. No other simplification is applied, see
+    /// [`LogicTree::simplify`] for that.
+    pub fn canonicalize(&self) -> Self {
+        match self {
+            Self::Terminal(_) => self.clone(),
+            Self::Gate { gate, .. } if is_associative(*gate) => {
+                let mut operands = Vec::new();
+                collect_operands(self, *gate, &mut operands);
+                operands.sort_by(compare);
+                let mut operands = operands.into_iter();
+                let first = operands.next().expect("a gate has operands");
+                operands.fold(first, |chain, operand| Self::gate(*gate, chain, operand))
+            }
+            Self::Gate { gate, left, right } => {
+                let mut left = left.canonicalize();
+                let mut right = right.canonicalize();
+                if compare(&left, &right) == Ordering::Greater {
+                    std::mem::swap(&mut left, &mut right);
+                }
+                Self::gate(*gate, left, right)
+            }
+        }
+    }
+
+    /// 64 bit FNV-1a hash of the canonical form.
+    ///
+    /// Unlike [`std::hash::Hash`], the value is fixed by this crate rather
+    /// than the standard library, so it is the same on every platform and can
+    /// be persisted. Terminals hash as a zero byte followed by their little
+    /// endian id, gates as their binary opcode followed by their inputs.
+    pub fn canonical_hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET_BASIS;
+        hash_into(&self.canonicalize(), &mut hash);
+        hash
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::str::FromStr;
+
+    fn canonical(source: &str) -> String {
+        LogicTree::from_str(source)
+            .unwrap()
+            .canonicalize()
+            .to_string()
+    }
+
+    #[test]
+    fn canonical_form() {
+        assert_eq!(canonical("1 AND 0"), "0 AND 1");
+        assert_eq!(canonical("2 AND (1 AND 0)"), "0 AND 1 AND 2");
+        assert_eq!(canonical("(3 OR 1) OR (2 OR 0)"), "0 OR 1 OR 2 OR 3");
+        assert_eq!(canonical("(1 NAND 0) NAND 2"), "2 NAND (0 NAND 1)");
+        assert_eq!(canonical("2 NOR (1 NOR 0)"), "2 NOR (0 NOR 1)");
+        // different gates are not merged into one chain
+        assert_eq!(canonical("(3 OR 2) AND 1 AND 0"), "0 AND 1 AND (2 OR 3)");
+        assert_eq!(canonical("1 XOR 0 XOR 1"), "0 XOR 1 XOR 1");
+    }
+
+    #[test]
+    fn equal_after_reordering() {
+        let this = LogicTree::from_str("(0 AND 1) OR (2 XOR (4 XOR 3))").unwrap();
+        let that = LogicTree::from_str("(3 XOR 2 XOR 4) OR (1 AND 0)").unwrap();
+        assert_ne!(this, that);
+        assert_eq!(this.canonicalize(), that.canonicalize());
+        assert_eq!(this.canonical_hash(), that.canonical_hash());
+
+        let other = LogicTree::from_str("(0 AND 1) OR (2 NOR (4 XOR 3))").unwrap();
+        assert_ne!(this.canonical_hash(), other.canonical_hash());
+    }
+
+    #[test]
+    fn stable_hash() {
+        // pinned, changing these breaks persisted hashes
+        assert_eq!(
+            LogicTree::Terminal(0).canonical_hash(),
+            0xe4bc_4fd9_252b_e94f
+        );
+        assert_eq!(
+            LogicTree::from_str("1 AND 0").unwrap().canonical_hash(),
+            0x37c7_16ec_38a4_871d
+        );
+    }
+
+    #[test]
+    fn preserves_semantics() {
+        for seed in 0..100 {
+            let tree = random_tree(seed, 5, 12);
+            let canonical = tree.canonicalize();
+            assert_eq!(canonical.canonicalize(), canonical);
+            for assignment in assignments(&tree.terminals()) {
+                assert_eq!(canonical.evaluate(&assignment), tree.evaluate(&assignment));
+            }
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::sat::solve;
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::collections::HashMap;
+
+/// Outcome of a logical comparison between two trees.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Holds,
+    /// An assignment of the terminals of both trees under which the
+    /// comparison fails.
+    Counterexample(HashMap<TerminalId, bool>),
+}
+
+impl Verdict {
+    pub fn holds(&self) -> bool {
+        matches!(self, Self::Holds)
+    }
+
+    pub fn counterexample(&self) -> Option<&HashMap<TerminalId, bool>> {
+        match self {
+            Self::Holds => None,
+            Self::Counterexample(assignment) => Some(assignment),
+        }
+    }
+}
+
+impl From<Option<HashMap<TerminalId, bool>>> for Verdict {
+    fn from(counterexample: Option<HashMap<TerminalId, bool>>) -> Self {
+        counterexample.map_or(Self::Holds, Self::Counterexample)
+    }
+}
+
+impl LogicTree {
+    /// Checks whether both trees evaluate to the same value under every
+    /// assignment.
+    ///
+    /// A counterexample makes exactly one of the trees evaluate to `true`.
+    pub fn equivalent(&self, other: &Self) -> Verdict {
+        let no_terminals = HashMap::new();
+        solve(&[(self, true), (other, false)], &no_terminals)
+            .or_else(|| solve(&[(self, false), (other, true)], &no_terminals))
+            .into()
+    }
+
+    /// Checks whether `other` evaluates to `true` whenever `self` does, i.e.
+    /// `self` is at least as strict as `other`.
+    ///
+    /// A counterexample makes `self` evaluate to `true` and `other` to
+    /// `false`.
+    pub fn implies(&self, other: &Self) -> Verdict {
+        solve(&[(self, true), (other, false)], &HashMap::new()).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::str::FromStr;
+
+    #[test]
+    fn equivalence() {
+        let pairs = [
+            ("0 NAND 1", "(0 NAND 0) OR (1 NAND 1)"),
+            ("0 NOR 1", "(0 NAND 0) AND (1 NAND 1)"),
+            ("0 XOR 1", "(0 OR 1) AND (0 NAND 1)"),
+            ("0 AND (0 OR 1)", "0"),
+            ("0 AND 1 OR 2", "2 OR (1 AND 0)"),
+        ];
+        for (a, b) in pairs {
+            let a = LogicTree::from_str(a).unwrap();
+            let b = LogicTree::from_str(b).unwrap();
+            assert!(a.equivalent(&b).holds(), "{} <=> {}", a, b);
+            assert!(b.equivalent(&a).holds(), "{} <=> {}", b, a);
+        }
+
+        let a = LogicTree::from_str("0 XOR 1").unwrap();
+        let b = LogicTree::from_str("0 NOR 1").unwrap();
+        let verdict = a.equivalent(&b);
+        let counterexample = verdict.counterexample().unwrap();
+        assert_eq!(counterexample.len(), 2);
+        assert_ne!(
+            a.evaluate(counterexample).unwrap(),
+            b.evaluate(counterexample).unwrap()
+        );
+    }
+
+    #[test]
+    fn implication() {
+        let stricter = LogicTree::from_str("0 AND 1 AND 2").unwrap();
+        let looser = LogicTree::from_str("0 AND (1 OR 3)").unwrap();
+        assert!(stricter.implies(&looser).holds());
+
+        let counterexample = match looser.implies(&stricter) {
+            Verdict::Counterexample(counterexample) => counterexample,
+            Verdict::Holds => panic!("should not hold"),
+        };
+        assert_eq!(counterexample.len(), 4);
+        assert!(looser.evaluate(&counterexample).unwrap());
+        assert!(!stricter.evaluate(&counterexample).unwrap());
+    }
+
+    #[test]
+    fn agrees_with_evaluation() {
+        for seed in 0..200 {
+            let a = random_tree(seed, 4, 6);
+            let b = random_tree(seed + 1000, 4, 6);
+            let terminals = a.terminals().union(&b.terminals()).copied().collect();
+            let (mut equivalent, mut implies) = (true, true);
+            for assignment in assignments(&terminals) {
+                let (x, y) = (
+                    a.evaluate(&assignment).unwrap(),
+                    b.evaluate(&assignment).unwrap(),
+                );
+                equivalent &= x == y;
+                implies &= !x || y;
+            }
+
+            let verdict = a.equivalent(&b);
+            assert_eq!(verdict.holds(), equivalent, "{} <=> {}", a, b);
+            if let Some(counterexample) = verdict.counterexample() {
+                assert_ne!(
+                    a.evaluate(counterexample).unwrap(),
+                    b.evaluate(counterexample).unwrap()
+                );
+            }
+
+            let verdict = a.implies(&b);
+            assert_eq!(verdict.holds(), implies, "{} => {}", a, b);
+            if let Some(counterexample) = verdict.counterexample() {
+                assert!(a.evaluate(counterexample).unwrap());
+                assert!(!b.evaluate(counterexample).unwrap());
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::bdd::{Bdd, ProbabilityError};
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::collections::HashMap;
+
+impl LogicTree {
+    /// Counts the assignments of the referenced terminals under which the tree
+    /// evaluates to `true`. Returns `None` if the count does not fit into an
+    /// `u128`.
+    pub fn model_count(&self) -> Option<u128> {
+        Bdd::from_tree(self).model_count()
+    }
+
+    /// Fraction of all assignments of the referenced terminals under which the
+    /// tree evaluates to `true`.
+    pub fn pass_fraction(&self) -> f64 {
+        let probabilities = self.terminals().into_iter().map(|id| (id, 0.5)).collect();
+        Bdd::from_tree(self)
+            .probability(&probabilities)
+            .expect("every terminal has a valid probability")
+    }
+
+    /// Probability of the tree evaluating to `true` when each terminal is
+    /// `true` independently with the given probability.
+    ///
+    /// Every referenced terminal needs a probability within `[0, 1]`.
+    pub fn pass_probability(
+        &self,
+        probabilities: &HashMap<TerminalId, f64>,
+    ) -> Result<f64, ProbabilityError> {
+        for terminal in self.terminals() {
+            let probability = *probabilities
+                .get(&terminal)
+                .ok_or(ProbabilityError::MissingProbability(terminal))?;
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(ProbabilityError::InvalidProbability {
+                    terminal,
+                    probability,
+                });
+            }
+        }
+        Bdd::from_tree(self).probability(probabilities)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::str::FromStr;
+
+    #[test]
+    fn model_count() {
+        let tree = LogicTree::from_str("0 AND 1").unwrap();
+        assert_eq!(tree.model_count(), Some(1));
+        let tree = LogicTree::from_str("0 OR 1 OR 2").unwrap();
+        assert_eq!(tree.model_count(), Some(7));
+        // terminal 1 does not matter but still doubles the assignments
+        let tree = LogicTree::from_str("0 OR (1 AND (1 XOR 1))").unwrap();
+        assert_eq!(tree.model_count(), Some(2));
+        let tree = LogicTree::from_str("0 XOR 0").unwrap();
+        assert_eq!(tree.model_count(), Some(0));
+
+        for seed in 0..200 {
+            let tree = random_tree(seed, 6, 12);
+            let expected = assignments(&tree.terminals())
+                .filter(|assignment| tree.evaluate(assignment).unwrap())
+                .count();
+            assert_eq!(tree.model_count(), Some(expected as u128), "{}", tree);
+            let fraction = expected as f64 / (1 << tree.terminals().len()) as f64;
+            assert!((tree.pass_fraction() - fraction).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn large_counts() {
+        let tree = (1..128).fold(LogicTree::terminal(0), |tree, id| tree.or(id.into()));
+        assert_eq!(tree.model_count(), Some(u128::MAX));
+        let tree = tree.or(128.into());
+        assert_eq!(tree.model_count(), None);
+        assert!((tree.pass_fraction() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn pass_probability() {
+        let tree = LogicTree::from_str("0 AND (1 OR 2)").unwrap();
+        let probabilities = [(0, 0.5), (1, 0.3), (2, 0.2)].into_iter().collect();
+        let expected = 0.5 * (1.0 - 0.7 * 0.8);
+        let probability = tree.pass_probability(&probabilities).unwrap();
+        assert!((probability - expected).abs() < 1e-12);
+
+        let tree = LogicTree::from_str("0 XOR 1").unwrap();
+        let probabilities = [(0, 0.9), (1, 0.2)].into_iter().collect();
+        let expected = 0.9 * 0.8 + 0.1 * 0.2;
+        let probability = tree.pass_probability(&probabilities).unwrap();
+        assert!((probability - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn invalid_probabilities() {
+        let tree = LogicTree::from_str("0 OR (1 AND (1 XOR 1))").unwrap();
+        match tree.pass_probability(&[(0, 0.5)].into_iter().collect()) {
+            Err(ProbabilityError::MissingProbability(1)) => {}
+            _ => panic!("terminal 1 should be missing"),
+        }
+        match tree.pass_probability(&[(0, 1.5), (1, 0.5)].into_iter().collect()) {
+            Err(ProbabilityError::InvalidProbability { terminal: 0, .. }) => {}
+            _ => panic!("terminal 0 should be invalid"),
+        }
+        let probability = tree
+            .pass_probability(&[(0, 0.25), (1, 0.5)].into_iter().collect())
+            .unwrap();
+        assert!((probability - 0.25).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,364 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+use thiserror::Error;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of the ids which tie every handle to the graph that created it.
+static NEXT_DAG_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum DagError {
+    #[error("node belongs to another graph")]
+    ForeignNode,
+}
+
+/// Handle of a node within a [`Dag`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DagNode {
+    dag: u64,
+    index: u32,
+}
+
+/// Contents of a node of a [`Dag`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DagEntry {
+    Terminal(TerminalId),
+    Gate {
+        gate: Gate,
+        left: DagNode,
+        right: DagNode,
+    },
+}
+
+/// Directed acyclic graph form of a [`LogicTree`], in which structurally
+/// identical subtrees are stored once.
+///
+/// Nodes are hash-consed, so two nodes of the same graph are equal trees
+/// exactly if their handles are equal. Inputs are always created before the
+/// gates using them. Note that equality is structural, `0 AND 1` and
+/// `1 AND 0` are different nodes unless the tree is canonicalized first, see
+/// [`LogicTree::canonicalize`].
+///
+/// Handles only refer to nodes of the graph that created them, passing them
+/// to any other graph, including a clone, fails with
+/// [`DagError::ForeignNode`].
+#[derive(Debug)]
+pub struct Dag {
+    id: u64,
+    nodes: Vec<DagEntry>,
+    unique: HashMap<DagEntry, DagNode>,
+    root: DagNode,
+}
+
+impl Dag {
+    pub fn from_tree(tree: &LogicTree) -> Self {
+        let id = NEXT_DAG_ID.fetch_add(1, Ordering::Relaxed);
+        let mut dag = Self {
+            id,
+            nodes: Vec::new(),
+            unique: HashMap::new(),
+            root: DagNode { dag: id, index: 0 },
+        };
+        dag.root = dag.build(tree);
+        dag
+    }
+
+    pub fn root(&self) -> DagNode {
+        self.root
+    }
+
+    pub fn set_root(&mut self, root: DagNode) -> Result<(), DagError> {
+        self.index(root)?;
+        self.root = root;
+        Ok(())
+    }
+
+    /// Number of distinct nodes, including those unreachable from the root.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Position of a node of this graph in `nodes`. Nodes are never removed,
+    /// so every handle of this graph is in bounds.
+    fn index(&self, node: DagNode) -> Result<usize, DagError> {
+        if node.dag == self.id {
+            Ok(node.index as usize)
+        } else {
+            Err(DagError::ForeignNode)
+        }
+    }
+
+    pub fn entry(&self, node: DagNode) -> Result<DagEntry, DagError> {
+        Ok(self.nodes[self.index(node)?])
+    }
+
+    fn intern(&mut self, entry: DagEntry) -> DagNode {
+        if let Some(node) = self.unique.get(&entry) {
+            return *node;
+        }
+        let node = DagNode {
+            dag: self.id,
+            index: self.nodes.len() as u32,
+        };
+        self.nodes.push(entry);
+        self.unique.insert(entry, node);
+        node
+    }
+
+    pub fn terminal(&mut self, id: TerminalId) -> DagNode {
+        self.intern(DagEntry::Terminal(id))
+    }
+
+    /// Combines two nodes of this graph with a gate.
+    pub fn gate(&mut self, gate: Gate, left: DagNode, right: DagNode) -> Result<DagNode, DagError> {
+        self.index(left)?;
+        self.index(right)?;
+        Ok(self.intern(DagEntry::Gate { gate, left, right }))
+    }
+
+    /// Adds a tree to the graph without changing the root.
+    pub fn build(&mut self, tree: &LogicTree) -> DagNode {
+        match tree {
+            LogicTree::Terminal(id) => self.terminal(*id),
+            LogicTree::Gate { gate, left, right } => {
+                let left = self.build(left);
+                let right = self.build(right);
+                self.intern(DagEntry::Gate {
+                    gate: *gate,
+                    left,
+                    right,
+                })
+            }
+        }
+    }
+
+    /// Evaluates the root, computing every shared node only once.
+    pub fn evaluate(&self, terminals: &HashMap<TerminalId, bool>) -> Result<bool, String> {
+        self.evaluate_node(self.root, terminals)
+    }
+
+    /// Evaluates a node without recursion, so arbitrarily deep graphs cannot
+    /// overflow the stack.
+    pub fn evaluate_node(
+        &self,
+        node: DagNode,
+        terminals: &HashMap<TerminalId, bool>,
+    ) -> Result<bool, String> {
+        let target = self.index(node).map_err(|error| error.to_string())?;
+        // inputs come before the gates using them, so no index exceeds `target`
+        let mut computed = vec![None; target + 1];
+        let mut stack = vec![target];
+        while let Some(&index) = stack.last() {
+            if computed[index].is_some() {
+                stack.pop();
+                continue;
+            }
+            match self.nodes[index] {
+                DagEntry::Terminal(id) => {
+                    let value = *terminals
+                        .get(&id)
+                        .ok_or_else(|| "Invalid terminals map".to_string())?;
+                    computed[index] = Some(value);
+                    stack.pop();
+                }
+                DagEntry::Gate { gate, left, right } => {
+                    let (left, right) = (left.index as usize, right.index as usize);
+                    match (computed[left], computed[right]) {
+                        (Some(left), Some(right)) => {
+                            computed[index] = Some(gate.apply(left, right));
+                            stack.pop();
+                        }
+                        // the gate stays on the stack until its inputs are done
+                        (left_value, right_value) => {
+                            if right_value.is_none() {
+                                stack.push(right);
+                            }
+                            if left_value.is_none() {
+                                stack.push(left);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(computed[target].expect("the target is computed last"))
+    }
+
+    /// Expands the root back into a tree. Shared nodes are copied for every
+    /// use, so the tree may be exponentially larger than the graph.
+    pub fn to_tree(&self) -> LogicTree {
+        self.expand(self.root.index as usize)
+    }
+
+    pub fn node_to_tree(&self, node: DagNode) -> Result<LogicTree, DagError> {
+        Ok(self.expand(self.index(node)?))
+    }
+
+    fn expand(&self, index: usize) -> LogicTree {
+        match self.nodes[index] {
+            DagEntry::Terminal(id) => LogicTree::Terminal(id),
+            DagEntry::Gate { gate, left, right } => LogicTree::gate(
+                gate,
+                self.expand(left.index as usize),
+                self.expand(right.index as usize),
+            ),
+        }
+    }
+}
+
+impl Clone for Dag {
+    /// Copies the graph under a new id, so that handles of either graph are
+    /// rejected by the other once they diverge.
+    fn clone(&self) -> Self {
+        let id = NEXT_DAG_ID.fetch_add(1, Ordering::Relaxed);
+        let retag = |node: DagNode| DagNode { dag: id, ..node };
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|entry| match *entry {
+                DagEntry::Terminal(id) => DagEntry::Terminal(id),
+                DagEntry::Gate { gate, left, right } => DagEntry::Gate {
+                    gate,
+                    left: retag(left),
+                    right: retag(right),
+                },
+            })
+            .collect::<Vec<_>>();
+        let unique = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let node = DagNode {
+                    dag: id,
+                    index: index as u32,
+                };
+                (*entry, node)
+            })
+            .collect();
+        Self {
+            id,
+            nodes,
+            unique,
+            root: retag(self.root),
+        }
+    }
+}
+
+impl LogicTree {
+    pub fn to_dag(&self) -> Dag {
+        Dag::from_tree(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::str::FromStr;
+
+    #[test]
+    fn shares_subtrees() {
+        let tree = LogicTree::from_str("(1 OR 2) AND 0 XOR (1 OR 2) AND (0 XOR (1 OR 2))").unwrap();
+        let dag = tree.to_dag();
+        // 0, 1, 2, 1 OR 2, 0 XOR (1 OR 2) and the three gates of the chain
+        assert_eq!(dag.node_count(), 8);
+        assert_eq!(tree.node_count(), 15);
+        assert_eq!(dag.to_tree(), tree);
+
+        let mut dag = Dag::from_tree(&LogicTree::from_str("1 OR 2").unwrap());
+        let shared = dag.root();
+        assert_eq!(dag.build(&LogicTree::from_str("(1 OR 2)").unwrap()), shared);
+        let zero = dag.terminal(0);
+        let root = dag.gate(Gate::Nand, zero, shared).unwrap();
+        assert_eq!(
+            dag.entry(root).unwrap(),
+            DagEntry::Gate {
+                gate: Gate::Nand,
+                left: zero,
+                right: shared
+            }
+        );
+        dag.set_root(root).unwrap();
+        assert_eq!(dag.to_tree().to_string(), "0 NAND (1 OR 2)");
+    }
+
+    #[test]
+    fn evaluation() {
+        for seed in 0..100 {
+            let tree = random_tree(seed, 4, 16);
+            let dag = tree.to_dag();
+            assert_eq!(dag.to_tree(), tree);
+            for assignment in assignments(&tree.terminals()) {
+                assert_eq!(dag.evaluate(&assignment), tree.evaluate(&assignment));
+            }
+        }
+        assert!(LogicTree::from_str("0 AND 1")
+            .unwrap()
+            .to_dag()
+            .evaluate(&[(0, true)].into_iter().collect())
+            .is_err());
+    }
+
+    #[test]
+    fn memoized_evaluation() {
+        // the expanded tree would have 2^100 leaves
+        let mut dag = Dag::from_tree(&LogicTree::Terminal(0));
+        let one = dag.terminal(1);
+        let mut node = dag.gate(Gate::Xor, dag.root(), one).unwrap();
+        for _ in 0..100 {
+            node = dag.gate(Gate::And, node, node).unwrap();
+        }
+        dag.set_root(node).unwrap();
+        let terminals = [(0, true), (1, false)].into_iter().collect();
+        assert!(dag.evaluate(&terminals).unwrap());
+        let terminals = [(0, true), (1, true)].into_iter().collect();
+        assert!(!dag.evaluate(&terminals).unwrap());
+    }
+
+    #[test]
+    fn deep_evaluation() {
+        let mut dag = Dag::from_tree(&LogicTree::Terminal(0));
+        let mut node = dag.root();
+        for id in 1..200_000 {
+            let terminal = dag.terminal(id % 7);
+            node = dag.gate(Gate::Xor, node, terminal).unwrap();
+        }
+        dag.set_root(node).unwrap();
+        let terminals = (0..7).map(|id| (id, id == 3)).collect();
+        // terminal 3 occurs 28_571 times
+        assert!(dag.evaluate(&terminals).unwrap());
+    }
+
+    #[test]
+    fn foreign_nodes() {
+        let tree = LogicTree::from_str("0 AND 1 OR 2").unwrap();
+        let mut dag = tree.to_dag();
+        let mut other = tree.to_dag();
+        let mut clone = dag.clone();
+        // every handle of the other graphs is in bounds
+        for foreign in [other.root(), clone.root(), other.terminal(0)] {
+            assert_eq!(dag.entry(foreign), Err(DagError::ForeignNode));
+            assert_eq!(dag.node_to_tree(foreign), Err(DagError::ForeignNode));
+            assert_eq!(dag.set_root(foreign), Err(DagError::ForeignNode));
+            let root = dag.root();
+            assert_eq!(
+                dag.gate(Gate::And, root, foreign),
+                Err(DagError::ForeignNode)
+            );
+            assert!(dag.evaluate_node(foreign, &HashMap::new()).is_err());
+        }
+        assert_eq!(dag.node_count(), 5);
+
+        let zero = clone.terminal(0);
+        let root = clone.root();
+        let extended = clone.gate(Gate::Nor, root, zero).unwrap();
+        assert_eq!(
+            clone.node_to_tree(extended).unwrap().to_string(),
+            "0 AND 1 OR 2 NOR 0"
+        );
+        assert_eq!(clone.to_tree(), tree);
+    }
+}
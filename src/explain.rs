@@ -0,0 +1,248 @@
+use crate::gate::Gate;
+use crate::sat::solve;
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+const INDENT: &str = "  ";
+
+/// Evaluation of every node of a tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Trace {
+    Terminal {
+        id: TerminalId,
+        value: bool,
+    },
+    Gate {
+        gate: Gate,
+        left: Box<Trace>,
+        right: Box<Trace>,
+        output: bool,
+    },
+}
+
+impl Trace {
+    fn new(tree: &LogicTree, terminals: &HashMap<TerminalId, bool>) -> Result<Self, String> {
+        let trace = match tree {
+            LogicTree::Terminal(id) => Self::Terminal {
+                id: *id,
+                value: *terminals
+                    .get(id)
+                    .ok_or_else(|| "Invalid terminals map".to_string())?,
+            },
+            LogicTree::Gate { gate, left, right } => {
+                let left = Self::new(left, terminals)?;
+                let right = Self::new(right, terminals)?;
+                Self::Gate {
+                    gate: *gate,
+                    output: gate.apply(left.output(), right.output()),
+                    left: Box::new(left),
+                    right: Box::new(right),
+                }
+            }
+        };
+        Ok(trace)
+    }
+
+    pub fn output(&self) -> bool {
+        match self {
+            Self::Terminal { value, .. } => *value,
+            Self::Gate { output, .. } => *output,
+        }
+    }
+
+    fn render(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+        core: &BTreeMap<TerminalId, bool>,
+    ) -> std::fmt::Result {
+        let indent = INDENT.repeat(depth);
+        match self {
+            Self::Terminal { id, value } => {
+                let marker = if core.contains_key(id) { " *" } else { "" };
+                writeln!(f, "{}{} = {}{}", indent, id, value, marker)
+            }
+            Self::Gate {
+                gate,
+                left,
+                right,
+                output,
+            } => {
+                writeln!(f, "{}{} => {}", indent, gate, output)?;
+                left.render(f, depth + 1, core)?;
+                right.render(f, depth + 1, core)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.render(f, 0, &BTreeMap::new())
+    }
+}
+
+/// Answers why a tree evaluated the way it did, see [`LogicTree::explain`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    pub result: bool,
+    pub trace: Trace,
+    /// A subset-minimal set of terminal values that forces the result on its
+    /// own, whatever the other terminals are.
+    pub core: BTreeMap<TerminalId, bool>,
+}
+
+impl std::fmt::Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "result: {}", self.result)?;
+        self.trace.render(f, 0, &self.core)?;
+        let mut core = String::new();
+        for (i, (id, value)) in self.core.iter().enumerate() {
+            if i > 0 {
+                core.push_str(", ");
+            }
+            let _ = write!(core, "{} = {}", id, value);
+        }
+        if core.is_empty() {
+            core.push_str("nothing, the result is constant");
+        }
+        writeln!(f, "decided by: {}", core)
+    }
+}
+
+impl LogicTree {
+    /// Evaluates the tree and explains the result with the output of every
+    /// gate and the terminals that decided it.
+    ///
+    /// The core is found by dropping terminals one by one, in ascending order,
+    /// as long as the remaining ones still force the result.
+    pub fn explain(&self, terminals: &HashMap<TerminalId, bool>) -> Result<Explanation, String> {
+        let trace = Trace::new(self, terminals)?;
+        let result = trace.output();
+
+        let mut core = self
+            .terminals()
+            .into_iter()
+            .map(|id| (id, terminals[&id]))
+            .collect::<HashMap<_, _>>();
+        for id in self.terminals() {
+            let value = core.remove(&id).unwrap();
+            if solve(&[(self, !result)], &core).is_some() {
+                core.insert(id, value);
+            }
+        }
+
+        Ok(Explanation {
+            result,
+            trace,
+            core: core.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::str::FromStr;
+
+    #[test]
+    fn explain_failure() {
+        let tree = LogicTree::from_str("0 AND 1 OR (2 AND 3)").unwrap();
+        let terminals = [(0, true), (1, false), (2, true), (3, false)]
+            .into_iter()
+            .collect();
+        let explanation = tree.explain(&terminals).unwrap();
+        assert!(!explanation.result);
+        assert_eq!(
+            explanation.core.into_iter().collect::<Vec<_>>(),
+            [(1, false), (3, false)]
+        );
+        match explanation.trace {
+            Trace::Gate {
+                gate: Gate::Or,
+                output: false,
+                ..
+            } => {}
+            _ => panic!("root should be a failed OR"),
+        }
+    }
+
+    #[test]
+    fn explain_success() {
+        let tree = LogicTree::from_str("0 OR 1 OR 2").unwrap();
+        let terminals = [(0, false), (1, true), (2, true)].into_iter().collect();
+        let explanation = tree.explain(&terminals).unwrap();
+        assert!(explanation.result);
+        // either one of the passed terminals suffices, the first is dropped
+        assert_eq!(
+            explanation.core.into_iter().collect::<Vec<_>>(),
+            [(2, true)]
+        );
+    }
+
+    #[test]
+    fn render() {
+        let tree = LogicTree::from_str("0 AND (1 NOR 2)").unwrap();
+        let terminals = [(0, true), (1, true), (2, false)].into_iter().collect();
+        let explanation = tree.explain(&terminals).unwrap();
+        assert_eq!(
+            explanation.to_string(),
+            "result: false\n\
+             AND => false\n\
+             \x20 0 = true\n\
+             \x20 NOR => false\n\
+             \x20   1 = true *\n\
+             \x20   2 = false\n\
+             decided by: 1 = true\n"
+        );
+        assert_eq!(
+            explanation.trace.to_string(),
+            "AND => false\n  0 = true\n  NOR => false\n    1 = true\n    2 = false\n"
+        );
+        assert!(tree.explain(&HashMap::new()).is_err());
+
+        let tree = LogicTree::from_str("0 XOR 0").unwrap();
+        let terminals = [(0, true)].into_iter().collect();
+        let explanation = tree.explain(&terminals).unwrap();
+        assert!(explanation.core.is_empty());
+        assert!(explanation
+            .to_string()
+            .ends_with("decided by: nothing, the result is constant\n"));
+    }
+
+    #[test]
+    fn core_forces_result() {
+        for seed in 0..50 {
+            let tree = random_tree(seed, 4, 8);
+            for terminals in assignments(&tree.terminals()) {
+                let explanation = tree.explain(&terminals).unwrap();
+                assert_eq!(explanation.result, tree.evaluate(&terminals).unwrap());
+                assert_eq!(explanation.trace.output(), explanation.result);
+                // every completion of the core gives the same result
+                let free = tree
+                    .terminals()
+                    .into_iter()
+                    .filter(|id| !explanation.core.contains_key(id))
+                    .collect();
+                for mut completion in assignments(&free) {
+                    completion.extend(explanation.core.iter().map(|(k, v)| (*k, *v)));
+                    assert_eq!(tree.evaluate(&completion).unwrap(), explanation.result);
+                }
+                // and dropping any core terminal breaks that
+                for id in explanation.core.keys() {
+                    let mut fixed = explanation
+                        .core
+                        .iter()
+                        .map(|(k, v)| (*k, *v))
+                        .collect::<HashMap<_, _>>();
+                    fixed.remove(id);
+                    assert!(solve(&[(&tree, !explanation.result)], &fixed).is_some());
+                }
+            }
+        }
+    }
+}
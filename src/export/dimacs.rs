@@ -0,0 +1,122 @@
+use crate::normal::Variable;
+use crate::token::LogicTree;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+impl LogicTree {
+    /// Renders the Tseitin encoding of the tree, see
+    /// [`LogicTree::to_tseitin_cnf`], in DIMACS CNF format.
+    ///
+    /// Terminals are numbered from 1 in ascending order, followed by the
+    /// auxiliary gate variables. The header comments list which variable
+    /// stands for which terminal.
+    pub fn to_dimacs(&self) -> String {
+        let cnf = self.to_tseitin_cnf();
+        let terminals = self.terminals();
+        let numbers = terminals
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (Variable::Terminal(*id), i + 1))
+            .collect::<HashMap<_, _>>();
+        let number = |variable: Variable| match variable {
+            Variable::Terminal(_) => numbers[&variable],
+            Variable::Auxiliary(n) => terminals.len() + n as usize + 1,
+        };
+        let variables = cnf
+            .clauses
+            .iter()
+            .flatten()
+            .map(|literal| number(literal.variable))
+            .max()
+            .unwrap_or(0);
+
+        let mut dimacs = String::from("c tseitin encoding of a requiem logic tree\n");
+        for (i, id) in terminals.iter().enumerate() {
+            let _ = writeln!(dimacs, "c terminal {} = {}", id, i + 1);
+        }
+        let _ = writeln!(dimacs, "p cnf {} {}", variables, cnf.clauses.len());
+        for clause in &cnf.clauses {
+            for literal in clause {
+                let sign = if literal.negated { "-" } else { "" };
+                let _ = write!(dimacs, "{}{} ", sign, number(literal.variable));
+            }
+            dimacs.push_str("0\n");
+        }
+        dimacs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sat::{Lit, Solver};
+    use crate::testing::{assignments, random_tree};
+    use crate::TerminalId;
+    use std::str::FromStr;
+
+    #[test]
+    fn format() {
+        let tree = LogicTree::from_str("3 AND 7").unwrap();
+        assert_eq!(
+            tree.to_dimacs(),
+            "c tseitin encoding of a requiem logic tree\n\
+             c terminal 3 = 1\n\
+             c terminal 7 = 2\n\
+             p cnf 3 4\n\
+             -3 1 0\n\
+             -3 2 0\n\
+             3 -1 -2 0\n\
+             3 0\n"
+        );
+    }
+
+    #[test]
+    fn agrees_with_evaluation() {
+        for seed in 0..100 {
+            let tree = random_tree(seed, 5, 12);
+            let dimacs = tree.to_dimacs();
+
+            let mut mapping = HashMap::new();
+            let mut variables = 0;
+            let mut clauses = Vec::new();
+            for line in dimacs.lines() {
+                if let Some(entry) = line.strip_prefix("c terminal ") {
+                    let (id, number) = entry.split_once(" = ").unwrap();
+                    mapping.insert(
+                        id.parse::<TerminalId>().unwrap(),
+                        number.parse::<usize>().unwrap(),
+                    );
+                } else if let Some(header) = line.strip_prefix("p cnf ") {
+                    variables = header.split(' ').next().unwrap().parse().unwrap();
+                } else if !line.starts_with('c') {
+                    let clause = line
+                        .split(' ')
+                        .map(|literal| literal.parse::<i64>().unwrap())
+                        .take_while(|literal| *literal != 0)
+                        .map(|literal| Lit::new(literal.unsigned_abs() as usize - 1, literal < 0))
+                        .collect::<Vec<_>>();
+                    clauses.push(clause);
+                }
+            }
+
+            // the formula with the terminals fixed is satisfiable exactly if
+            // the tree evaluates to true
+            for values in assignments(&tree.terminals()).step_by(3) {
+                let mut solver = Solver::new(variables);
+                for clause in &clauses {
+                    solver.add_clause(clause);
+                }
+                for (id, value) in &values {
+                    solver.add_clause(&[Lit::new(mapping[id] - 1, !value)]);
+                }
+                assert_eq!(
+                    solver.solve().is_some(),
+                    tree.evaluate(&values).unwrap(),
+                    "{}",
+                    tree
+                );
+            }
+        }
+    }
+}
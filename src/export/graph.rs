@@ -0,0 +1,212 @@
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const PASSED_COLOR: &str = "#9be59b";
+const FAILED_COLOR: &str = "#f29b9b";
+
+#[derive(Clone, Debug, Default)]
+pub struct GraphOptions {
+    /// Labels of terminals, e.g. requirement names. Terminals without a label
+    /// show their id.
+    pub labels: HashMap<TerminalId, String>,
+    /// Terminal values used to color every node green or red by its output.
+    /// Nodes depending on a missing terminal stay uncolored.
+    pub evaluation: Option<HashMap<TerminalId, bool>>,
+}
+
+struct Node {
+    label: String,
+    terminal: bool,
+    value: Option<bool>,
+}
+
+/// Flattened tree with nodes in preorder, edges point from parent to child.
+struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    fn new(tree: &LogicTree, options: &GraphOptions) -> Self {
+        let mut graph = Self {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+        graph.add(tree, options);
+        graph
+    }
+
+    /// Appends the nodes of a subtree and returns its value.
+    fn add(&mut self, tree: &LogicTree, options: &GraphOptions) -> Option<bool> {
+        let index = self.nodes.len();
+        match tree {
+            LogicTree::Terminal(id) => {
+                let value = options
+                    .evaluation
+                    .as_ref()
+                    .and_then(|evaluation| evaluation.get(id).copied());
+                self.nodes.push(Node {
+                    label: options
+                        .labels
+                        .get(id)
+                        .cloned()
+                        .unwrap_or_else(|| id.to_string()),
+                    terminal: true,
+                    value,
+                });
+                value
+            }
+            LogicTree::Gate { gate, left, right } => {
+                self.nodes.push(Node {
+                    label: gate.to_string(),
+                    terminal: false,
+                    value: None,
+                });
+                self.edges.push((index, self.nodes.len()));
+                let left_value = self.add(left, options);
+                self.edges.push((index, self.nodes.len()));
+                let right_value = self.add(right, options);
+                let value = left_value
+                    .zip(right_value)
+                    .map(|(left, right)| gate.apply(left, right));
+                self.nodes[index].value = value;
+                value
+            }
+        }
+    }
+}
+
+impl LogicTree {
+    /// Renders the tree as a Graphviz DOT digraph.
+    pub fn to_dot(&self, options: &GraphOptions) -> String {
+        let graph = Graph::new(self, options);
+        let mut dot = String::from("digraph LogicTree {\n");
+        for (i, node) in graph.nodes.iter().enumerate() {
+            let label = node.label.replace('\\', "\\\\").replace('"', "\\\"");
+            let shape = if node.terminal { "ellipse" } else { "box" };
+            let _ = write!(dot, "    n{} [label=\"{}\", shape={}", i, label, shape);
+            if let Some(value) = node.value {
+                let color = if value { PASSED_COLOR } else { FAILED_COLOR };
+                let _ = write!(dot, ", style=filled, fillcolor=\"{}\"", color);
+            }
+            dot.push_str("];\n");
+        }
+        for (parent, child) in &graph.edges {
+            let _ = writeln!(dot, "    n{} -> n{};", parent, child);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the tree as a top-down Mermaid flowchart.
+    pub fn to_mermaid(&self, options: &GraphOptions) -> String {
+        let graph = Graph::new(self, options);
+        let mut mermaid = String::from("flowchart TD\n");
+        for (i, node) in graph.nodes.iter().enumerate() {
+            // `#` starts entity codes, so it is escaped before adding entities
+            let label = node
+                .label
+                .replace('#', "#35;")
+                .replace('"', "#quot;")
+                .replace("\r\n", "<br>")
+                .replace(['\r', '\n'], "<br>");
+            if node.terminal {
+                let _ = writeln!(mermaid, "    n{}([\"{}\"])", i, label);
+            } else {
+                let _ = writeln!(mermaid, "    n{}[\"{}\"]", i, label);
+            }
+        }
+        for (parent, child) in &graph.edges {
+            let _ = writeln!(mermaid, "    n{} --> n{}", parent, child);
+        }
+        if options.evaluation.is_some() {
+            let _ = writeln!(mermaid, "    classDef passed fill:{}", PASSED_COLOR);
+            let _ = writeln!(mermaid, "    classDef failed fill:{}", FAILED_COLOR);
+            for (i, node) in graph.nodes.iter().enumerate() {
+                if let Some(value) = node.value {
+                    let class = if value { "passed" } else { "failed" };
+                    let _ = writeln!(mermaid, "    class n{} {}", i, class);
+                }
+            }
+        }
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn dot() {
+        let tree = LogicTree::from_str("0 AND (1 OR 2)").unwrap();
+        assert_eq!(
+            tree.to_dot(&GraphOptions::default()),
+            "digraph LogicTree {\n\
+             \x20   n0 [label=\"AND\", shape=box];\n\
+             \x20   n1 [label=\"0\", shape=ellipse];\n\
+             \x20   n2 [label=\"OR\", shape=box];\n\
+             \x20   n3 [label=\"1\", shape=ellipse];\n\
+             \x20   n4 [label=\"2\", shape=ellipse];\n\
+             \x20   n0 -> n1;\n\
+             \x20   n0 -> n2;\n\
+             \x20   n2 -> n3;\n\
+             \x20   n2 -> n4;\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn dot_with_labels_and_colors() {
+        let tree = LogicTree::from_str("0 OR 1").unwrap();
+        let options = GraphOptions {
+            labels: [(0, "holds \"NFT\"".to_string())].into_iter().collect(),
+            evaluation: Some([(0, false), (1, true)].into_iter().collect()),
+        };
+        let dot = tree.to_dot(&options);
+        assert!(dot.contains("n0 [label=\"OR\", shape=box, style=filled, fillcolor=\"#9be59b\"];"));
+        assert!(dot.contains(
+            "n1 [label=\"holds \\\"NFT\\\"\", shape=ellipse, style=filled, fillcolor=\"#f29b9b\"];"
+        ));
+        assert!(dot.contains("n2 [label=\"1\", shape=ellipse, style=filled"));
+    }
+
+    #[test]
+    fn mermaid() {
+        let tree = LogicTree::from_str("0 NOR 1").unwrap();
+        assert_eq!(
+            tree.to_mermaid(&GraphOptions::default()),
+            "flowchart TD\n\
+             \x20   n0[\"NOR\"]\n\
+             \x20   n1([\"0\"])\n\
+             \x20   n2([\"1\"])\n\
+             \x20   n0 --> n1\n\
+             \x20   n0 --> n2\n"
+        );
+
+        let options = GraphOptions {
+            labels: [(1, "role \"admin\"".to_string())].into_iter().collect(),
+            evaluation: Some([(0, false)].into_iter().collect()),
+        };
+        let mermaid = tree.to_mermaid(&options);
+        assert!(mermaid.contains("n2([\"role #quot;admin#quot;\"])"));
+        assert!(mermaid.contains("classDef passed fill:#9be59b\n"));
+        assert!(mermaid.contains("class n1 failed\n"));
+        // terminal 1 is missing, so the gate has no value either
+        assert!(!mermaid.contains("class n0"));
+        assert!(!mermaid.contains("class n2"));
+
+        let options = GraphOptions {
+            labels: [(0, "#quot; member\nof #1".to_string())]
+                .into_iter()
+                .collect(),
+            evaluation: None,
+        };
+        let mermaid = tree.to_mermaid(&options);
+        assert!(mermaid.contains("n1([\"#35;quot; member<br>of #35;1\"])\n"));
+    }
+}
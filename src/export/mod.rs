@@ -0,0 +1,7 @@
+mod dimacs;
+mod graph;
+mod smt;
+mod sql;
+
+pub use graph::GraphOptions;
+pub use sql::{SqlError, SqlOptions};
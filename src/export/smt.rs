@@ -0,0 +1,116 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::fmt::Write;
+
+fn constant_name(id: TerminalId) -> String {
+    format!("t{}", id)
+}
+
+fn term(tree: &LogicTree, output: &mut String) {
+    match tree {
+        LogicTree::Terminal(id) => output.push_str(&constant_name(*id)),
+        LogicTree::Gate { gate, left, right } => {
+            let (negated, operator) = match gate {
+                Gate::And => (false, "and"),
+                Gate::Or => (false, "or"),
+                Gate::Nand => (true, "and"),
+                Gate::Nor => (true, "or"),
+                Gate::Xor => (false, "xor"),
+            };
+            if negated {
+                output.push_str("(not ");
+            }
+            let _ = write!(output, "({} ", operator);
+            term(left, output);
+            output.push(' ');
+            term(right, output);
+            output.push(')');
+            if negated {
+                output.push(')');
+            }
+        }
+    }
+}
+
+impl LogicTree {
+    /// Renders an SMT-LIB2 script asserting the tree, with a boolean constant
+    /// `t<id>` declared for each terminal.
+    pub fn to_smtlib(&self) -> String {
+        let mut script = String::from("(set-logic QF_UF)\n");
+        for id in self.terminals() {
+            let _ = writeln!(script, "(declare-const {} Bool)", constant_name(id));
+        }
+        script.push_str("(assert ");
+        term(self, &mut script);
+        script.push_str(")\n(check-sat)\n");
+        script
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    /// Evaluates the boolean SMT-LIB2 term at the start of `source` and
+    /// returns it with the unconsumed rest.
+    fn evaluate<'a>(source: &'a str, values: &HashMap<TerminalId, bool>) -> (bool, &'a str) {
+        let source = source.trim_start();
+        if let Some(rest) = source.strip_prefix('(') {
+            let (operator, mut rest) = rest.split_once(' ').unwrap();
+            let mut operands = Vec::new();
+            while !rest.trim_start().starts_with(')') {
+                let (value, remaining) = evaluate(rest, values);
+                operands.push(value);
+                rest = remaining;
+            }
+            let value = match operator {
+                "not" => !operands[0],
+                "and" => operands.iter().all(|x| *x),
+                "or" => operands.iter().any(|x| *x),
+                "xor" => operands.iter().fold(false, |acc, x| acc ^ x),
+                _ => panic!("unknown operator {}", operator),
+            };
+            (value, &rest.trim_start()[1..])
+        } else {
+            let end = source.find([' ', ')']).unwrap_or(source.len());
+            let id = source[1..end].parse::<TerminalId>().unwrap();
+            (values[&id], &source[end..])
+        }
+    }
+
+    #[test]
+    fn script() {
+        let tree = LogicTree::from_str("0 AND (1 NOR 2) XOR 0").unwrap();
+        assert_eq!(
+            tree.to_smtlib(),
+            "(set-logic QF_UF)\n\
+             (declare-const t0 Bool)\n\
+             (declare-const t1 Bool)\n\
+             (declare-const t2 Bool)\n\
+             (assert (xor (and t0 (not (or t1 t2))) t0))\n\
+             (check-sat)\n"
+        );
+    }
+
+    #[test]
+    fn agrees_with_evaluation() {
+        for seed in 0..100 {
+            let tree = random_tree(seed, 5, 12);
+            let script = tree.to_smtlib();
+            let assertion = script
+                .lines()
+                .find_map(|line| line.strip_prefix("(assert "))
+                .unwrap();
+            for values in assignments(&tree.terminals()).step_by(3) {
+                let (value, rest) = evaluate(assertion, &values);
+                assert_eq!(rest, ")");
+                assert_eq!(value, tree.evaluate(&values).unwrap(), "{}", tree);
+            }
+        }
+    }
+}
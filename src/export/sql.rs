@@ -0,0 +1,216 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+use thiserror::Error;
+
+use std::collections::HashMap;
+
+#[derive(Error, Debug)]
+pub enum SqlError {
+    #[error("terminal {0} has no column")]
+    UnmappedTerminal(TerminalId),
+    #[error("invalid identifier {0:?}")]
+    InvalidIdentifier(String),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SqlOptions {
+    /// Boolean column holding the result of each terminal.
+    pub columns: HashMap<TerminalId, String>,
+    /// Table name or alias qualifying every column.
+    pub table: Option<String>,
+}
+
+/// Quotes an identifier as in standard SQL, which both Postgres and SQLite
+/// accept.
+fn quote(identifier: &str) -> Result<String, SqlError> {
+    if identifier.is_empty() || identifier.contains('\0') {
+        return Err(SqlError::InvalidIdentifier(identifier.to_string()));
+    }
+    Ok(format!("\"{}\"", identifier.replace('"', "\"\"")))
+}
+
+fn expression(
+    tree: &LogicTree,
+    columns: &HashMap<TerminalId, String>,
+    output: &mut String,
+) -> Result<(), SqlError> {
+    match tree {
+        LogicTree::Terminal(id) => {
+            output.push_str(columns.get(id).ok_or(SqlError::UnmappedTerminal(*id))?);
+        }
+        LogicTree::Gate { gate, left, right } => {
+            let (negated, operator) = match gate {
+                Gate::And => (false, "AND"),
+                Gate::Or => (false, "OR"),
+                Gate::Nand => (true, "AND"),
+                Gate::Nor => (true, "OR"),
+                // booleans are comparable in Postgres, and integers in SQLite
+                Gate::Xor => (false, "<>"),
+            };
+            // `NOT` binds looser than `<>`, so it is parenthesized as a whole
+            if negated {
+                output.push_str("(NOT (");
+            } else {
+                output.push('(');
+            }
+            expression(left, columns, output)?;
+            output.push(' ');
+            output.push_str(operator);
+            output.push(' ');
+            expression(right, columns, output)?;
+            output.push_str(if negated { "))" } else { ")" });
+        }
+    }
+    Ok(())
+}
+
+impl LogicTree {
+    /// Renders the tree as a boolean SQL expression for a `WHERE` clause,
+    /// evaluated against one boolean column per terminal.
+    ///
+    /// The expression only uses `AND`, `OR`, `NOT` and `<>`, so it runs on
+    /// both Postgres and SQLite. SQLite has no boolean type, so there XOR
+    /// compares the columns as integers and requires them to hold `0` and
+    /// `1`, as `FALSE` and `TRUE` store them. Rows with a `NULL` requirement
+    /// column may evaluate to `NULL` and are therefore not selected.
+    pub fn to_sql(&self, options: &SqlOptions) -> Result<String, SqlError> {
+        let table = options.table.as_deref().map(quote).transpose()?;
+        let columns = options
+            .columns
+            .iter()
+            .map(|(id, column)| {
+                let column = quote(column)?;
+                let qualified = match &table {
+                    Some(table) => format!("{}.{}", table, column),
+                    None => column,
+                };
+                Ok((*id, qualified))
+            })
+            .collect::<Result<HashMap<_, _>, SqlError>>()?;
+        let mut sql = String::new();
+        expression(self, &columns, &mut sql)?;
+        Ok(sql)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use rusqlite::Connection;
+    use std::str::FromStr;
+
+    fn columns(ids: impl Iterator<Item = TerminalId>) -> HashMap<TerminalId, String> {
+        ids.map(|id| (id, format!("req_{}", id))).collect()
+    }
+
+    #[test]
+    fn expression() {
+        let tree = LogicTree::from_str("0 AND (1 NOR 2) XOR 3").unwrap();
+        let options = SqlOptions {
+            columns: columns(0..4),
+            table: None,
+        };
+        assert_eq!(
+            tree.to_sql(&options).unwrap(),
+            r#"(("req_0" AND (NOT ("req_1" OR "req_2"))) <> "req_3")"#
+        );
+
+        let options = SqlOptions {
+            columns: [(0, "has \"role\"".to_string()), (1, "x".to_string())]
+                .into_iter()
+                .collect(),
+            table: Some("members".to_string()),
+        };
+        let tree = LogicTree::from_str("0 NAND 1").unwrap();
+        assert_eq!(
+            tree.to_sql(&options).unwrap(),
+            r#"(NOT ("members"."has ""role""" AND "members"."x"))"#
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let tree = LogicTree::from_str("0 OR 1").unwrap();
+        let options = SqlOptions {
+            columns: columns(0..1),
+            table: None,
+        };
+        match tree.to_sql(&options) {
+            Err(SqlError::UnmappedTerminal(1)) => {}
+            _ => panic!("terminal 1 should be unmapped"),
+        }
+        let options = SqlOptions {
+            columns: [(0, "a\0".to_string()), (1, "b".to_string())]
+                .into_iter()
+                .collect(),
+            table: None,
+        };
+        assert!(tree.to_sql(&options).is_err());
+    }
+
+    #[test]
+    fn selects_passing_members() {
+        for seed in 0..100 {
+            let tree = random_tree(seed, 5, 12);
+            let ids = tree.terminals();
+            let options = SqlOptions {
+                columns: ids
+                    .iter()
+                    .map(|id| (*id, format!("req \"{}\"", id)))
+                    .collect(),
+                table: Some("requirement \"results\"".to_string()),
+            };
+            let sql = tree.to_sql(&options).unwrap();
+
+            // one member per possible combination of requirement results
+            let connection = Connection::open_in_memory().unwrap();
+            let columns = ids
+                .iter()
+                .map(|id| format!(r#", "req ""{}""" BOOLEAN"#, id))
+                .collect::<String>();
+            connection
+                .execute(
+                    &format!(
+                        r#"CREATE TABLE "requirement ""results""" (member INTEGER{})"#,
+                        columns
+                    ),
+                    [],
+                )
+                .unwrap();
+            let members = assignments(&ids).collect::<Vec<_>>();
+            for (i, member) in members.iter().enumerate() {
+                let values = ids
+                    .iter()
+                    .map(|id| if member[id] { ", TRUE" } else { ", FALSE" })
+                    .collect::<String>();
+                connection
+                    .execute(
+                        &format!(
+                            r#"INSERT INTO "requirement ""results""" VALUES ({}{})"#,
+                            i, values
+                        ),
+                        [],
+                    )
+                    .unwrap();
+            }
+
+            let mut statement = connection
+                .prepare(&format!(
+                    r#"SELECT member FROM "requirement ""results""" WHERE {} ORDER BY member"#,
+                    sql
+                ))
+                .unwrap();
+            let selected = statement
+                .query_map([], |row| row.get::<_, usize>(0))
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let expected = (0..members.len())
+                .filter(|&i| tree.evaluate(&members[i]).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(selected, expected, "{}", sql);
+        }
+    }
+}
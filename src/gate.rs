@@ -6,7 +6,7 @@ pub const NAND: &str = "NAND";
 pub const NOR: &str = "NOR";
 pub const XOR: &str = "XOR";
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Gate {
     And,
     Or,
@@ -15,6 +15,19 @@ pub enum Gate {
     Xor,
 }
 
+impl Gate {
+    /// Computes the output of the gate for the given inputs.
+    pub fn apply(self, left: bool, right: bool) -> bool {
+        match self {
+            Self::And => left && right,
+            Self::Or => left || right,
+            Self::Nand => !(left && right),
+            Self::Nor => !(left || right),
+            Self::Xor => left ^ right,
+        }
+    }
+}
+
 impl FromStr for Gate {
     type Err = anyhow::Error;
     fn from_str(input: &str) -> Result<Self, Self::Err> {
@@ -0,0 +1,344 @@
+//! Versioned JSON representation of a [`LogicTree`], independent from the
+//! string syntax.
+//!
+//! ```json
+//! {
+//!     "version": 1,
+//!     "root": {
+//!         "gate": "AND",
+//!         "children": [{ "terminal": 0 }, { "terminal": 1 }]
+//!     }
+//! }
+//! ```
+//!
+//! Gates are named like in the string syntax. `AND`, `OR` and `XOR` accept
+//! two or more children which are combined left to right, `NAND` and `NOR`
+//! take exactly two. Exported documents always use two children per gate.
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use std::str::FromStr;
+
+pub const JSON_FORMAT_VERSION: u64 = 1;
+
+#[derive(Error, Debug)]
+pub enum JsonError {
+    #[error("invalid JSON: {0}")]
+    Syntax(#[from] serde_json::Error),
+    #[error("unsupported format version {0}")]
+    UnsupportedVersion(u64),
+    #[error("missing field `{field}` at {path}")]
+    MissingField { path: String, field: &'static str },
+    #[error("unknown field `{field}` at {path}")]
+    UnknownField { path: String, field: String },
+    #[error("invalid value at {path}: {reason}")]
+    InvalidValue { path: String, reason: &'static str },
+    #[error("unknown gate `{gate}` at {path}")]
+    UnknownGate { path: String, gate: String },
+    #[error("{gate} at {path} takes {expected} children, found {found}")]
+    InvalidArity {
+        path: String,
+        gate: Gate,
+        expected: &'static str,
+        found: usize,
+    },
+    #[error("node at {path} needs exactly one of `gate` and `terminal`")]
+    InvalidNode { path: String },
+}
+
+fn node_to_value(tree: &LogicTree) -> Value {
+    match tree {
+        LogicTree::Terminal(id) => json!({ "terminal": id }),
+        LogicTree::Gate { gate, left, right } => json!({
+            "gate": gate.to_string(),
+            "children": [node_to_value(left), node_to_value(right)],
+        }),
+    }
+}
+
+fn as_object<'a>(value: &'a Value, path: &str) -> Result<&'a Map<String, Value>, JsonError> {
+    value.as_object().ok_or_else(|| JsonError::InvalidValue {
+        path: path.to_string(),
+        reason: "expected an object",
+    })
+}
+
+fn check_fields(
+    object: &Map<String, Value>,
+    allowed: &[&str],
+    path: &str,
+) -> Result<(), JsonError> {
+    match object.keys().find(|key| !allowed.contains(&key.as_str())) {
+        Some(field) => Err(JsonError::UnknownField {
+            path: path.to_string(),
+            field: field.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn node_from_value(value: &Value, path: &str) -> Result<LogicTree, JsonError> {
+    let object = as_object(value, path)?;
+    match (object.get("terminal"), object.get("gate")) {
+        (Some(terminal), None) => {
+            check_fields(object, &["terminal"], path)?;
+            let id = terminal
+                .as_u64()
+                .and_then(|id| TerminalId::try_from(id).ok())
+                .ok_or_else(|| JsonError::InvalidValue {
+                    path: format!("{}/terminal", path),
+                    reason: "expected a terminal id",
+                })?;
+            Ok(LogicTree::Terminal(id))
+        }
+        (None, Some(gate)) => {
+            check_fields(object, &["gate", "children"], path)?;
+            let name = gate.as_str().ok_or_else(|| JsonError::InvalidValue {
+                path: format!("{}/gate", path),
+                reason: "expected a string",
+            })?;
+            let gate = Gate::from_str(name).map_err(|_| JsonError::UnknownGate {
+                path: format!("{}/gate", path),
+                gate: name.to_string(),
+            })?;
+            let children = object
+                .get("children")
+                .ok_or_else(|| JsonError::MissingField {
+                    path: path.to_string(),
+                    field: "children",
+                })?
+                .as_array()
+                .ok_or_else(|| JsonError::InvalidValue {
+                    path: format!("{}/children", path),
+                    reason: "expected an array",
+                })?;
+
+            let (expected, valid) = match gate {
+                Gate::Nand | Gate::Nor => ("2", children.len() == 2),
+                Gate::And | Gate::Or | Gate::Xor => ("2 or more", children.len() >= 2),
+            };
+            if !valid {
+                return Err(JsonError::InvalidArity {
+                    path: path.to_string(),
+                    gate,
+                    expected,
+                    found: children.len(),
+                });
+            }
+
+            let mut children = children
+                .iter()
+                .enumerate()
+                .map(|(i, child)| node_from_value(child, &format!("{}/children/{}", path, i)));
+            let first = children.next().unwrap()?;
+            children.try_fold(first, |tree, child| Ok(LogicTree::gate(gate, tree, child?)))
+        }
+        _ => Err(JsonError::InvalidNode {
+            path: path.to_string(),
+        }),
+    }
+}
+
+impl LogicTree {
+    pub fn to_json_value(&self) -> Value {
+        json!({
+            "version": JSON_FORMAT_VERSION,
+            "root": node_to_value(self),
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    pub fn from_json_value(value: &Value) -> Result<Self, JsonError> {
+        let object = as_object(value, "")?;
+        check_fields(object, &["version", "root"], "")?;
+        let version = object
+            .get("version")
+            .ok_or(JsonError::MissingField {
+                path: String::new(),
+                field: "version",
+            })?
+            .as_u64()
+            .ok_or(JsonError::InvalidValue {
+                path: "/version".to_string(),
+                reason: "expected an unsigned integer",
+            })?;
+        if version != JSON_FORMAT_VERSION {
+            return Err(JsonError::UnsupportedVersion(version));
+        }
+        let root = object.get("root").ok_or(JsonError::MissingField {
+            path: String::new(),
+            field: "root",
+        })?;
+        node_from_value(root, "/root")
+    }
+
+    pub fn from_json(source: &str) -> Result<Self, JsonError> {
+        Self::from_json_value(&serde_json::from_str(source)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::random_tree;
+
+    fn is_equal_discriminant(this: &JsonError, that: &JsonError) -> bool {
+        std::mem::discriminant(this) == std::mem::discriminant(that)
+    }
+
+    #[test]
+    fn export() {
+        let tree = LogicTree::from_str("0 NOR 1").unwrap();
+        assert_eq!(
+            tree.to_json(),
+            r#"{"root":{"children":[{"terminal":0},{"terminal":1}],"gate":"NOR"},"version":1}"#
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        for seed in 0..100 {
+            let tree = random_tree(seed, 8, 12);
+            let imported = LogicTree::from_json(&tree.to_json()).unwrap();
+            assert_eq!(imported.to_string(), tree.to_string());
+        }
+    }
+
+    #[test]
+    fn variadic_gates() {
+        let source = r#"{"version": 1, "root": {"gate": "OR", "children": [
+            {"terminal": 0},
+            {"terminal": 1},
+            {"gate": "AND", "children": [{"terminal": 2}, {"terminal": 3}, {"terminal": 4}]}
+        ]}}"#;
+        let tree = LogicTree::from_json(source).unwrap();
+        assert_eq!(tree.to_string(), "0 OR 1 OR (2 AND 3 AND 4)");
+    }
+
+    #[test]
+    fn invalid_documents() {
+        let cases = [
+            (
+                "{",
+                JsonError::Syntax(serde_json::from_str::<Value>("").unwrap_err()),
+            ),
+            (
+                r#"[]"#,
+                JsonError::InvalidValue {
+                    path: String::new(),
+                    reason: "",
+                },
+            ),
+            (
+                r#"{"version": 2, "root": {"terminal": 0}}"#,
+                JsonError::UnsupportedVersion(2),
+            ),
+            (
+                r#"{"root": {"terminal": 0}}"#,
+                JsonError::MissingField {
+                    path: String::new(),
+                    field: "",
+                },
+            ),
+            (
+                r#"{"version": 1}"#,
+                JsonError::MissingField {
+                    path: String::new(),
+                    field: "",
+                },
+            ),
+            (
+                r#"{"version": 1, "root": {"terminal": 0}, "extra": true}"#,
+                JsonError::UnknownField {
+                    path: String::new(),
+                    field: String::new(),
+                },
+            ),
+            (
+                r#"{"version": 1, "root": {"terminal": -1}}"#,
+                JsonError::InvalidValue {
+                    path: String::new(),
+                    reason: "",
+                },
+            ),
+            (
+                r#"{"version": 1, "root": {"terminal": 4294967296}}"#,
+                JsonError::InvalidValue {
+                    path: String::new(),
+                    reason: "",
+                },
+            ),
+            (
+                r#"{"version": 1, "root": {"gate": "IMPLIES", "children": []}}"#,
+                JsonError::UnknownGate {
+                    path: String::new(),
+                    gate: String::new(),
+                },
+            ),
+            (
+                r#"{"version": 1, "root": {"gate": "AND"}}"#,
+                JsonError::MissingField {
+                    path: String::new(),
+                    field: "",
+                },
+            ),
+            (
+                r#"{"version": 1, "root": {"terminal": 0, "gate": "AND"}}"#,
+                JsonError::InvalidNode {
+                    path: String::new(),
+                },
+            ),
+            (
+                r#"{"version": 1, "root": {}}"#,
+                JsonError::InvalidNode {
+                    path: String::new(),
+                },
+            ),
+        ];
+        for (source, expected) in cases {
+            let error = LogicTree::from_json(source).unwrap_err();
+            assert!(
+                is_equal_discriminant(&error, &expected),
+                "{}: {}",
+                source,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn error_paths() {
+        let source = r#"{"version": 1, "root": {"gate": "AND", "children": [
+            {"terminal": 0},
+            {"gate": "NAND", "children": [{"terminal": 1}, {"terminal": 2}, {"terminal": 3}]}
+        ]}}"#;
+        let error = LogicTree::from_json(source).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "NAND at /root/children/1 takes 2 children, found 3"
+        );
+
+        let source = r#"{"version": 1, "root": {"gate": "OR", "children": [
+            {"terminal": 0},
+            {"terminal": "1"}
+        ]}}"#;
+        let error = LogicTree::from_json(source).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value at /root/children/1/terminal: expected a terminal id"
+        );
+
+        let source = r#"{"version": 1, "root": {"gate": "XOR", "children": [{"terminal": 0}]}}"#;
+        let error = LogicTree::from_json(source).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "XOR at /root takes 2 or more children, found 1"
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Conversion between [`LogicTree`] and the boolean subset of
+//! [JsonLogic](https://jsonlogic.com).
+//!
+//! Terminals are `{"var": "<id>"}` lookups into data holding boolean
+//! requirement results. Gates map to JsonLogic as follows:
+//!
+//! | gate | JsonLogic |
+//! |:-:|:-:|
+//! | `a AND b` | `{"and": [a, b]}` |
+//! | `a OR b` | `{"or": [a, b]}` |
+//! | `a NAND b` | `{"!": {"and": [a, b]}}` |
+//! | `a NOR b` | `{"!": {"or": [a, b]}}` |
+//! | `a XOR b` | `{"!=": [a, b]}` |
+//!
+//! On import `!` of any other rule is folded into the rule's gate, or
+//! expressed as `x NAND x` for a variable. `!` of `!x` cancels out, `==` is
+//! imported as a negated XOR and `!!` is dropped.
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum JsonLogicError {
+    #[error("invalid JSON: {0}")]
+    Syntax(#[from] serde_json::Error),
+    #[error("unsupported operator `{operator}` at {path}")]
+    UnsupportedOperator { path: String, operator: String },
+    #[error("invalid arguments of `{operator}` at {path}: {reason}")]
+    InvalidArguments {
+        path: String,
+        operator: &'static str,
+        reason: &'static str,
+    },
+    #[error("invalid variable at {path}, expected a terminal id")]
+    InvalidVariable { path: String },
+    #[error("invalid rule at {path}: {reason}")]
+    InvalidRule { path: String, reason: &'static str },
+}
+
+/// Collects the operands of a left leaning chain of the same gate.
+fn flatten<'a>(tree: &'a LogicTree, chained: Gate, operands: &mut Vec<&'a LogicTree>) {
+    match tree {
+        LogicTree::Gate { gate, left, right } if *gate == chained => {
+            flatten(left, chained, operands);
+            operands.push(right);
+        }
+        _ => operands.push(tree),
+    }
+}
+
+fn to_value(tree: &LogicTree) -> Value {
+    match tree {
+        LogicTree::Terminal(id) => json!({ "var": id.to_string() }),
+        LogicTree::Gate { gate, left, right } => match gate {
+            Gate::And | Gate::Or => {
+                let mut operands = Vec::new();
+                flatten(tree, *gate, &mut operands);
+                let operands = operands.into_iter().map(to_value).collect::<Vec<_>>();
+                let operator = if *gate == Gate::And { "and" } else { "or" };
+                json!({ operator: operands })
+            }
+            Gate::Nand => json!({ "!": { "and": [to_value(left), to_value(right)] } }),
+            Gate::Nor => json!({ "!": { "or": [to_value(left), to_value(right)] } }),
+            Gate::Xor => json!({ "!=": [to_value(left), to_value(right)] }),
+        },
+    }
+}
+
+/// Arguments of an operation, a single non-array argument may be given
+/// without the wrapping array.
+fn arguments(value: &Value) -> &[Value] {
+    match value {
+        Value::Array(arguments) => arguments,
+        argument => std::slice::from_ref(argument),
+    }
+}
+
+fn from_value(value: &Value, path: &str) -> Result<LogicTree, JsonLogicError> {
+    let object = match value {
+        Value::Object(object) => object,
+        Value::Bool(_) => {
+            return Err(JsonLogicError::InvalidRule {
+                path: path.to_string(),
+                reason: "constants are not supported",
+            })
+        }
+        _ => {
+            return Err(JsonLogicError::InvalidRule {
+                path: path.to_string(),
+                reason: "expected an operation",
+            })
+        }
+    };
+    let mut entries = object.iter();
+    let (operator, argument) = match (entries.next(), entries.next()) {
+        (Some(entry), None) => entry,
+        _ => {
+            return Err(JsonLogicError::InvalidRule {
+                path: path.to_string(),
+                reason: "an operation needs exactly one operator",
+            })
+        }
+    };
+    let path = format!("{}/{}", path, operator);
+    let arguments = arguments(argument);
+    let operand = |i: usize| from_value(&arguments[i], &format!("{}/{}", path, i));
+    let invalid = |operator, reason| JsonLogicError::InvalidArguments {
+        path: path.clone(),
+        operator,
+        reason,
+    };
+
+    match operator.as_str() {
+        "var" => {
+            let id = match arguments {
+                [Value::String(id)] => id.parse::<TerminalId>().ok(),
+                [Value::Number(id)] => id.as_u64().and_then(|id| TerminalId::try_from(id).ok()),
+                _ => None,
+            };
+            id.map(LogicTree::Terminal)
+                .ok_or(JsonLogicError::InvalidVariable { path })
+        }
+        "and" | "or" => {
+            let gate = if operator == "and" {
+                Gate::And
+            } else {
+                Gate::Or
+            };
+            if arguments.is_empty() {
+                return Err(invalid(
+                    if gate == Gate::And { "and" } else { "or" },
+                    "expected at least one argument",
+                ));
+            }
+            let first = operand(0)?;
+            (1..arguments.len()).try_fold(first, |tree, i| {
+                Ok(LogicTree::gate(gate, tree, operand(i)?))
+            })
+        }
+        "!" => {
+            if arguments.len() != 1 {
+                return Err(invalid("!", "expected one argument"));
+            }
+            Ok(match operand(0)? {
+                // `!` of `!x`, which was imported as `x NAND x`
+                LogicTree::Gate {
+                    gate: Gate::Nand,
+                    left,
+                    right,
+                } if left == right => *left,
+                tree => tree.not(),
+            })
+        }
+        "!!" => {
+            if arguments.len() != 1 {
+                return Err(invalid("!!", "expected one argument"));
+            }
+            operand(0)
+        }
+        "!=" | "!==" | "==" | "===" => {
+            if arguments.len() != 2 {
+                let operator = match operator.as_str() {
+                    "!=" => "!=",
+                    "!==" => "!==",
+                    "==" => "==",
+                    _ => "===",
+                };
+                return Err(invalid(operator, "expected two arguments"));
+            }
+            let xor = operand(0)?.xor(operand(1)?);
+            Ok(if operator.starts_with("!=") {
+                xor
+            } else {
+                xor.not()
+            })
+        }
+        _ => Err(JsonLogicError::UnsupportedOperator {
+            path,
+            operator: operator.clone(),
+        }),
+    }
+}
+
+impl LogicTree {
+    pub fn to_json_logic_value(&self) -> Value {
+        to_value(self)
+    }
+
+    pub fn to_json_logic(&self) -> String {
+        self.to_json_logic_value().to_string()
+    }
+
+    pub fn from_json_logic_value(value: &Value) -> Result<Self, JsonLogicError> {
+        from_value(value, "")
+    }
+
+    pub fn from_json_logic(source: &str) -> Result<Self, JsonLogicError> {
+        Self::from_json_logic_value(&serde_json::from_str(source)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::random_tree;
+    use std::str::FromStr;
+
+    fn is_equal_discriminant(this: &JsonLogicError, that: &JsonLogicError) -> bool {
+        std::mem::discriminant(this) == std::mem::discriminant(that)
+    }
+
+    #[test]
+    fn export() {
+        let tree = LogicTree::from_str("0 AND 1 AND (2 OR 3)").unwrap();
+        assert_eq!(
+            tree.to_json_logic(),
+            r#"{"and":[{"var":"0"},{"var":"1"},{"or":[{"var":"2"},{"var":"3"}]}]}"#
+        );
+        let tree = LogicTree::from_str("0 NAND 1 XOR (2 NOR 3)").unwrap();
+        assert_eq!(
+            tree.to_json_logic(),
+            r#"{"!=":[{"!":{"and":[{"var":"0"},{"var":"1"}]}},{"!":{"or":[{"var":"2"},{"var":"3"}]}}]}"#
+        );
+    }
+
+    #[test]
+    fn import() {
+        let source = r#"{"or": [
+            {"var": "0"},
+            {"var": 1},
+            {"!": {"var": ["2"]}},
+            {"==": [{"var": "3"}, {"!!": [{"var": "4"}]}]}
+        ]}"#;
+        let tree = LogicTree::from_json_logic(source).unwrap();
+        assert_eq!(
+            tree.to_string(),
+            "0 OR 1 OR (2 NAND 2) OR (3 XOR (4 NAND 4))"
+        );
+        let tree = LogicTree::from_json_logic(r#"{"and": [{"var": "7"}]}"#).unwrap();
+        assert_eq!(tree.to_string(), "7");
+    }
+
+    #[test]
+    fn nested_negation() {
+        // well below the recursion limit of serde_json
+        let nest = |rule: &str, depth: usize| {
+            (0..depth).fold(rule.to_string(), |rule, _| format!(r#"{{"!": {}}}"#, rule))
+        };
+        let tree = LogicTree::from_json_logic(&nest(r#"{"var": "0"}"#, 100)).unwrap();
+        assert_eq!(tree.to_string(), "0");
+        let tree = LogicTree::from_json_logic(&nest(r#"{"var": "0"}"#, 101)).unwrap();
+        assert_eq!(tree.to_string(), "0 NAND 0");
+
+        let rule = r#"{"==": [{"var": "0"}, {"!=": [{"var": "1"}, {"var": "2"}]}]}"#;
+        for depth in [100, 101] {
+            let tree = LogicTree::from_json_logic(&nest(rule, depth)).unwrap();
+            assert!(tree.node_count() <= 7);
+            let expected = LogicTree::from_json_logic(&nest(rule, depth % 2)).unwrap();
+            assert!(tree.equivalent(&expected).holds());
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for seed in 0..100 {
+            let tree = random_tree(seed, 6, 12);
+            let imported = LogicTree::from_json_logic(&tree.to_json_logic()).unwrap();
+            assert!(imported.equivalent(&tree).holds(), "{}", tree);
+            assert_eq!(imported.node_count(), tree.node_count());
+        }
+    }
+
+    #[test]
+    fn unsupported() {
+        let cases = [
+            (
+                r#"{"if": [{"var": "0"}, {"var": "1"}, {"var": "2"}]}"#,
+                "if",
+            ),
+            (r#"{"and": [{"var": "0"}, {">": [{"var": "1"}, 3]}]}"#, ">"),
+            (r#"{"in": ["a", {"var": "0"}]}"#, "in"),
+        ];
+        for (source, operator) in cases {
+            match LogicTree::from_json_logic(source).unwrap_err() {
+                JsonLogicError::UnsupportedOperator {
+                    operator: found, ..
+                } => {
+                    assert_eq!(found, operator)
+                }
+                error => panic!("unexpected error {}", error),
+            }
+        }
+        let error =
+            LogicTree::from_json_logic(r#"{"or": [{"var": "0"}, {"<": [1, 2]}]}"#).unwrap_err();
+        assert_eq!(error.to_string(), "unsupported operator `<` at /or/1/<");
+    }
+
+    #[test]
+    fn invalid_rules() {
+        let cases = [
+            (
+                "{",
+                JsonLogicError::Syntax(serde_json::from_str::<Value>("").unwrap_err()),
+            ),
+            (
+                "true",
+                JsonLogicError::InvalidRule {
+                    path: String::new(),
+                    reason: "",
+                },
+            ),
+            (
+                "3",
+                JsonLogicError::InvalidRule {
+                    path: String::new(),
+                    reason: "",
+                },
+            ),
+            (
+                r#"{"and": [], "or": []}"#,
+                JsonLogicError::InvalidRule {
+                    path: String::new(),
+                    reason: "",
+                },
+            ),
+            (
+                r#"{"and": []}"#,
+                JsonLogicError::InvalidArguments {
+                    path: String::new(),
+                    operator: "",
+                    reason: "",
+                },
+            ),
+            (
+                r#"{"!=": [{"var": "0"}]}"#,
+                JsonLogicError::InvalidArguments {
+                    path: String::new(),
+                    operator: "",
+                    reason: "",
+                },
+            ),
+            (
+                r#"{"var": "name"}"#,
+                JsonLogicError::InvalidVariable {
+                    path: String::new(),
+                },
+            ),
+            (
+                r#"{"var": -1}"#,
+                JsonLogicError::InvalidVariable {
+                    path: String::new(),
+                },
+            ),
+        ];
+        for (source, expected) in cases {
+            let error = LogicTree::from_json_logic(source).unwrap_err();
+            assert!(
+                is_equal_discriminant(&error, &expected),
+                "{}: {}",
+                source,
+                error
+            );
+        }
+    }
+}
@@ -1,10 +1,52 @@
 #![deny(clippy::all)]
 #![deny(clippy::dbg_macro)]
 
+mod bdd;
+mod binary;
+mod build;
+mod canonical;
+mod compare;
+mod count;
+mod dag;
+mod explain;
+mod export;
 mod gate;
+mod json;
+mod jsonlogic;
+mod merge;
+mod minimize;
+mod normal;
+mod remap;
+mod sat;
+mod simplify;
+mod stats;
+mod suggest;
+mod table;
+#[cfg(test)]
+mod testing;
 mod token;
+mod visit;
 
+pub use bdd::{Bdd, BddError, BddNode, ProbabilityError, VariableOrder};
+pub use binary::{BinaryError, BINARY_FORMAT_VERSION, MAX_BINARY_DEPTH};
+pub use compare::Verdict;
+pub use dag::{Dag, DagEntry, DagError, DagNode};
+pub use explain::{Explanation, Trace};
+pub use export::{GraphOptions, SqlError, SqlOptions};
 pub use gate::Gate;
-pub use token::{LogicTree, ParseError};
+pub use json::{JsonError, JSON_FORMAT_VERSION};
+pub use jsonlogic::JsonLogicError;
+pub use merge::{MergeError, Merged};
+pub use minimize::{MinimizeOptions, MAX_EXACT_TERMINALS};
+pub use normal::{Cnf, Dnf, Literal, Variable};
+pub use remap::RemapError;
+pub use simplify::{Rule, Simplified};
+pub use suggest::{SuggestOptions, Suggestion};
+pub use table::{Row, Rows, TruthTable, TruthTableError, MAX_TRUTH_TABLE_TERMINALS};
+pub use token::{ArenaEntry, ArenaNode, ArenaTree, LogicTree, ParseError, Syntax};
+pub use visit::{
+    walk_gate, walk_gate_mut, walk_tree, walk_tree_mut, Fold, Postorder, Preorder, Visitor,
+    VisitorMut,
+};
 
 pub type TerminalId = u32;
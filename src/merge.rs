@@ -0,0 +1,144 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+use thiserror::Error;
+
+use std::collections::HashMap;
+
+#[derive(Error, Debug)]
+pub enum MergeError {
+    #[error("offset {offset} collides with terminal {terminal} of the first expression")]
+    OffsetCollision {
+        offset: TerminalId,
+        terminal: TerminalId,
+    },
+    #[error("terminal {terminal} overflows when offset by {offset}")]
+    Overflow {
+        offset: TerminalId,
+        terminal: TerminalId,
+    },
+    #[error("terminal {0} of the first expression leaves no ids for the second")]
+    NoFreeTerminals(TerminalId),
+}
+
+/// The result of merging two trees.
+#[derive(Clone, Debug)]
+pub struct Merged {
+    pub tree: LogicTree,
+    /// Maps the terminals of the second tree to their ids in the merged tree.
+    pub mapping: HashMap<TerminalId, TerminalId>,
+}
+
+impl LogicTree {
+    /// Combines two trees with `gate`, shifting the terminals of `other` right
+    /// after the largest terminal of `self`.
+    ///
+    /// This keeps the requirements consistent as long as the requirements of
+    /// `self` are exactly the ones it references, i.e. its requirements vector
+    /// has a length of `max terminal + 1`. Otherwise use
+    /// [`LogicTree::merge_with_offset`] with the length of the vector.
+    pub fn merge(&self, gate: Gate, other: &Self) -> Result<Merged, MergeError> {
+        let max = self.terminals().into_iter().next_back().unwrap_or_default();
+        let offset = max.checked_add(1).ok_or(MergeError::NoFreeTerminals(max))?;
+        self.merge_with_offset(gate, other, offset)
+    }
+
+    /// Combines two trees with `gate`, adding `offset` to every terminal of
+    /// `other`.
+    ///
+    /// When concatenating two requirements vectors, `offset` should be the
+    /// length of the first one.
+    pub fn merge_with_offset(
+        &self,
+        gate: Gate,
+        other: &Self,
+        offset: TerminalId,
+    ) -> Result<Merged, MergeError> {
+        if let Some(terminal) = self.terminals().range(offset..).next() {
+            return Err(MergeError::OffsetCollision {
+                offset,
+                terminal: *terminal,
+            });
+        }
+
+        let mapping = other
+            .terminals()
+            .into_iter()
+            .map(|terminal| {
+                terminal
+                    .checked_add(offset)
+                    .map(|new_id| (terminal, new_id))
+                    .ok_or(MergeError::Overflow { offset, terminal })
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let mut other = other.clone();
+        other.remap_terminals(&mapping);
+
+        Ok(Merged {
+            tree: Self::gate(gate, self.clone(), other),
+            mapping,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn merge_after_max() {
+        let left = LogicTree::from_str("0 AND 1").unwrap();
+        let right = LogicTree::from_str("0 OR (2 XOR 1)").unwrap();
+        let merged = left.merge(Gate::And, &right).unwrap();
+        assert_eq!(merged.tree.to_string(), "0 AND 1 AND (2 OR (4 XOR 3))");
+        assert_eq!(merged.mapping.len(), 3);
+        assert_eq!(merged.mapping[&0], 2);
+        assert_eq!(merged.mapping[&1], 3);
+        assert_eq!(merged.mapping[&2], 4);
+    }
+
+    #[test]
+    fn merge_with_requirements_length() {
+        // the first gate has 4 requirements but only references two of them
+        let left = LogicTree::from_str("0 NOR 2").unwrap();
+        let right = LogicTree::from_str("1").unwrap();
+        let merged = left.merge_with_offset(Gate::Or, &right, 4).unwrap();
+        assert_eq!(merged.tree.to_string(), "0 NOR 2 OR 5");
+        assert_eq!(merged.mapping[&1], 5);
+    }
+
+    #[test]
+    fn merge_errors() {
+        let left = LogicTree::from_str("0 AND 3").unwrap();
+        let right = LogicTree::from_str("0").unwrap();
+        match left.merge_with_offset(Gate::Or, &right, 3) {
+            Err(MergeError::OffsetCollision {
+                offset: 3,
+                terminal: 3,
+            }) => {}
+            _ => panic!("should collide"),
+        }
+
+        let right = LogicTree::from_str(&TerminalId::MAX.to_string()).unwrap();
+        match left.merge(Gate::Or, &right) {
+            Err(MergeError::Overflow {
+                offset: 4,
+                terminal: TerminalId::MAX,
+            }) => {}
+            _ => panic!("should overflow"),
+        }
+
+        match right.merge(Gate::Or, &left) {
+            Err(MergeError::NoFreeTerminals(TerminalId::MAX)) => {}
+            _ => panic!("should have no free terminals"),
+        }
+        assert_eq!(
+            right.merge(Gate::And, &right).unwrap_err().to_string(),
+            format!(
+                "terminal {} of the first expression leaves no ids for the second",
+                TerminalId::MAX
+            )
+        );
+    }
+}
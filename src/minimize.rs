@@ -0,0 +1,537 @@
+use crate::gate::Gate;
+use crate::normal::Literal;
+use crate::sat::solve;
+use crate::token::LogicTree;
+use crate::visit::Fold;
+use crate::TerminalId;
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Upper bound on the number of partial solutions of Petrick's method, above
+/// which the exact minimization gives up.
+const PETRICK_LIMIT: usize = 4096;
+
+/// Most terminals minimized exactly, whatever the configured threshold.
+pub const MAX_EXACT_TERMINALS: usize = 12;
+
+#[derive(Clone, Copy, Debug)]
+pub struct MinimizeOptions {
+    /// Trees with at most this many terminals are minimized exactly with the
+    /// Quine-McCluskey algorithm and Petrick's method, larger trees use an
+    /// Espresso style heuristic.
+    ///
+    /// The exact algorithm enumerates every assignment and every implicant,
+    /// both of which grow exponentially with the terminals. At 12 terminals
+    /// it may already take a second in a release build, so values above
+    /// [`MAX_EXACT_TERMINALS`] are treated as that. Trees for which Petrick's
+    /// method needs more than 4096 partial solutions fall back to the
+    /// heuristic as well, in which case the result may not be minimal.
+    pub exact_threshold: usize,
+}
+
+impl Default for MinimizeOptions {
+    fn default() -> Self {
+        Self {
+            exact_threshold: 10,
+        }
+    }
+}
+
+/// Product term over the terminals of the exact minimization, where bit `i`
+/// stands for the `i`-th terminal. Only bits set in `mask` are constrained.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Cube {
+    mask: u64,
+    value: u64,
+}
+
+impl Cube {
+    fn covers(&self, minterm: u64) -> bool {
+        minterm & self.mask == self.value
+    }
+
+    fn literals(&self, terminals: &[TerminalId]) -> Vec<Literal> {
+        terminals
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.mask & (1 << i) != 0)
+            .map(|(i, id)| Literal {
+                variable: *id,
+                negated: self.value & (1 << i) == 0,
+            })
+            .collect()
+    }
+}
+
+fn prime_implicants(minterms: &[u64], width: usize) -> Vec<Cube> {
+    let full = if width == 64 {
+        u64::MAX
+    } else {
+        (1 << width) - 1
+    };
+    let mut current = minterms
+        .iter()
+        .map(|minterm| Cube {
+            mask: full,
+            value: *minterm,
+        })
+        .collect::<HashSet<_>>();
+    let mut primes = Vec::new();
+
+    while !current.is_empty() {
+        let mut combined = HashSet::new();
+        let mut used = HashSet::new();
+        for cube in &current {
+            for i in 0..width {
+                let bit = 1 << i;
+                if cube.mask & bit == 0 || cube.value & bit != 0 {
+                    continue;
+                }
+                let partner = Cube {
+                    mask: cube.mask,
+                    value: cube.value | bit,
+                };
+                if current.contains(&partner) {
+                    combined.insert(Cube {
+                        mask: cube.mask & !bit,
+                        value: cube.value,
+                    });
+                    used.insert(*cube);
+                    used.insert(partner);
+                }
+            }
+        }
+        primes.extend(current.into_iter().filter(|cube| !used.contains(cube)));
+        current = combined;
+    }
+    primes.sort_unstable();
+    primes
+}
+
+/// Selects a cheapest set of prime implicants covering every minterm, taking
+/// the essential ones first and solving the rest with Petrick's method.
+///
+/// Returns `None` when Petrick's method needs more than [`PETRICK_LIMIT`]
+/// partial solutions.
+fn select_cover(primes: &[Cube], minterms: &[u64]) -> Option<Vec<Cube>> {
+    let covering = |minterm: u64| {
+        (0..primes.len())
+            .filter(|&i| primes[i].covers(minterm))
+            .collect::<Vec<_>>()
+    };
+    let mut selected = BTreeSet::new();
+    for minterm in minterms {
+        if let [only] = covering(*minterm)[..] {
+            selected.insert(only);
+        }
+    }
+
+    let cost = |product: &BTreeSet<usize>| {
+        let literals = product
+            .iter()
+            .map(|i| primes[*i].mask.count_ones())
+            .sum::<u32>();
+        (product.len(), literals)
+    };
+    let mut products = vec![BTreeSet::new()];
+    for minterm in minterms {
+        if selected.iter().any(|i| primes[*i].covers(*minterm)) {
+            continue;
+        }
+        let options = covering(*minterm);
+        let mut next = Vec::new();
+        for product in products {
+            if options.iter().any(|i| product.contains(i)) {
+                next.push(product);
+                continue;
+            }
+            for option in &options {
+                let mut extended = product.clone();
+                extended.insert(*option);
+                next.push(extended);
+            }
+        }
+        next.sort_unstable_by_key(|product| product.len());
+        next.dedup();
+        let mut absorbed: Vec<BTreeSet<usize>> = Vec::with_capacity(next.len());
+        for product in next {
+            if !absorbed.iter().any(|kept| kept.is_subset(&product)) {
+                absorbed.push(product);
+            }
+        }
+        if absorbed.len() > PETRICK_LIMIT {
+            return None;
+        }
+        products = absorbed;
+    }
+
+    let best = products
+        .into_iter()
+        .min_by_key(|product| cost(product))
+        .unwrap_or_default();
+    Some(
+        selected
+            .into_iter()
+            .chain(best)
+            .map(|i| primes[i])
+            .collect(),
+    )
+}
+
+/// Truth table of a tree over at most [`MAX_EXACT_TERMINALS`] terminals, with
+/// one bit per assignment where bit `i` of the assignment's index is the
+/// value of the `i`-th terminal.
+struct TruthBits<'a> {
+    terminals: &'a [TerminalId],
+    words: usize,
+}
+
+impl Fold for TruthBits<'_> {
+    type Output = Vec<u64>;
+
+    fn fold_terminal(&mut self, id: TerminalId) -> Vec<u64> {
+        // the pattern of the first six terminals repeats in every word
+        const PATTERNS: [u64; 6] = [
+            0xaaaa_aaaa_aaaa_aaaa,
+            0xcccc_cccc_cccc_cccc,
+            0xf0f0_f0f0_f0f0_f0f0,
+            0xff00_ff00_ff00_ff00,
+            0xffff_0000_ffff_0000,
+            0xffff_ffff_0000_0000,
+        ];
+        let i = self
+            .terminals
+            .binary_search(&id)
+            .expect("every terminal is listed");
+        (0..self.words)
+            .map(|word| match PATTERNS.get(i) {
+                Some(pattern) => *pattern,
+                None if word >> (i - 6) & 1 == 1 => u64::MAX,
+                None => 0,
+            })
+            .collect()
+    }
+
+    fn fold_gate(&mut self, gate: Gate, mut left: Vec<u64>, right: Vec<u64>) -> Vec<u64> {
+        for (left, right) in left.iter_mut().zip(right) {
+            *left = match gate {
+                Gate::And => *left & right,
+                Gate::Or => *left | right,
+                Gate::Nand => !(*left & right),
+                Gate::Nor => !(*left | right),
+                Gate::Xor => *left ^ right,
+            };
+        }
+        left
+    }
+}
+
+/// Minimal sum of products of the tree, or of its negation, over sorted
+/// terminals. Returns `None` when Petrick's method gives up.
+fn exact_cover(
+    tree: &LogicTree,
+    terminals: &[TerminalId],
+    negated: bool,
+) -> Option<Vec<Vec<Literal>>> {
+    let rows = 1_u64 << terminals.len();
+    let bits = tree.fold(&mut TruthBits {
+        terminals,
+        words: rows.div_ceil(64) as usize,
+    });
+    let minterms = (0..rows)
+        .filter(|minterm| (bits[(minterm / 64) as usize] >> (minterm % 64) & 1 == 1) != negated)
+        .collect::<Vec<_>>();
+    let primes = prime_implicants(&minterms, terminals.len());
+    let cover = select_cover(&primes, &minterms)?;
+    Some(cover.iter().map(|cube| cube.literals(terminals)).collect())
+}
+
+fn fixed(term: &[Literal]) -> HashMap<TerminalId, bool> {
+    term.iter()
+        .map(|literal| (literal.variable, !literal.negated))
+        .collect()
+}
+
+/// Smallest node count of a lowered cover, every term needs its literals as
+/// leaves joined by one gate less than there are leaves.
+fn lower_bound(cover: &[Vec<Literal>]) -> usize {
+    (2 * cover.iter().map(Vec::len).sum::<usize>()).saturating_sub(1)
+}
+
+/// Sum of products of the tree, or of its negation, built without
+/// enumerating minterms or distributing the tree.
+///
+/// Terms start from assignments the SAT solver finds outside of the current
+/// cover and are expanded into prime implicants by dropping literals while
+/// the term still implies the function. Redundant terms are dropped at the
+/// end. Returns `None` once the cover cannot be lowered into fewer than
+/// `budget` nodes, which bounds the number of solver calls.
+fn heuristic_cover(
+    tree: &LogicTree,
+    terminals: &[TerminalId],
+    negated: bool,
+    budget: usize,
+) -> Option<Vec<Vec<Literal>>> {
+    let mut cover: Vec<Vec<Literal>> = Vec::new();
+    loop {
+        let others = sum_of_products(&cover);
+        let mut constraints = vec![(tree, !negated)];
+        if let Some(others) = &others {
+            constraints.push((others, false));
+        }
+        let Some(model) = solve(&constraints, &HashMap::new()) else {
+            break;
+        };
+
+        // expand: drop literals while the term still implies the function
+        let mut term = terminals
+            .iter()
+            .map(|id| Literal {
+                variable: *id,
+                negated: !model[id],
+            })
+            .collect::<Vec<_>>();
+        let mut i = 0;
+        while i < term.len() {
+            let removed = term.remove(i);
+            if solve(&[(tree, negated)], &fixed(&term)).is_some() {
+                term.insert(i, removed);
+                i += 1;
+            }
+        }
+        if term.is_empty() {
+            // the function is constant
+            return Some(vec![term]);
+        }
+        cover.push(term);
+        if lower_bound(&cover) >= budget {
+            return None;
+        }
+    }
+
+    // irredundant: drop terms covered by the others, largest first
+    cover.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    let mut i = cover.len();
+    while i > 0 && cover.len() > 1 {
+        i -= 1;
+        let term = cover.remove(i);
+        let covered = sum_of_products(&cover)
+            .map(|others| solve(&[(&others, false)], &fixed(&term)).is_none())
+            .unwrap_or(false);
+        if !covered {
+            cover.insert(i, term);
+        }
+    }
+    Some(cover)
+}
+
+/// Flips the outermost gate of the tree to negate it.
+fn negate(tree: LogicTree) -> LogicTree {
+    match tree {
+        LogicTree::Gate { gate, left, right } if gate != Gate::Xor => {
+            let gate = match gate {
+                Gate::And => Gate::Nand,
+                Gate::Or => Gate::Nor,
+                Gate::Nand => Gate::And,
+                Gate::Nor => Gate::Or,
+                Gate::Xor => unreachable!(),
+            };
+            LogicTree::gate(gate, *left, *right)
+        }
+        tree => tree.not(),
+    }
+}
+
+fn chain(terminals: impl Iterator<Item = TerminalId>, gate: Gate) -> Option<LogicTree> {
+    terminals
+        .map(LogicTree::terminal)
+        .reduce(|left, right| LogicTree::gate(gate, left, right))
+}
+
+/// Lowers a nonempty term into `p1 AND p2 ... AND (n1 OR n2 ...  NOR nk)`.
+fn product(term: &[Literal]) -> LogicTree {
+    let positive = term.iter().filter(|l| !l.negated).map(|l| l.variable);
+    let negative = term.iter().filter(|l| l.negated).map(|l| l.variable);
+    let positive = chain(positive, Gate::And);
+    let negative = chain(negative, Gate::Or).map(negate);
+    match (positive, negative) {
+        (Some(positive), Some(negative)) => positive.and(negative),
+        (Some(tree), None) | (None, Some(tree)) => tree,
+        (None, None) => unreachable!("empty terms are constants"),
+    }
+}
+
+/// Lowers a cover without empty terms, returns `None` for an empty cover.
+fn sum_of_products(cover: &[Vec<Literal>]) -> Option<LogicTree> {
+    cover
+        .iter()
+        .map(|term| product(term))
+        .reduce(|left, right| left.or(right))
+}
+
+/// Lowers the cover of a function, or of its negation, into a tree.
+fn lower(cover: &[Vec<Literal>], negated: bool, witness: TerminalId) -> LogicTree {
+    if cover.iter().any(Vec::is_empty) {
+        return LogicTree::constant(!negated, witness);
+    }
+    match sum_of_products(cover) {
+        Some(tree) if negated => negate(tree),
+        Some(tree) => tree,
+        None => LogicTree::constant(negated, witness),
+    }
+}
+
+impl LogicTree {
+    /// Returns an equivalent tree with at most as many nodes, using the
+    /// default [`MinimizeOptions`].
+    pub fn minimize(&self) -> Self {
+        self.minimize_with(&MinimizeOptions::default())
+    }
+
+    /// Returns an equivalent tree with at most as many nodes.
+    ///
+    /// Both the sum of products of the tree and the negated sum of products
+    /// of its complement are minimized, and the smallest of them and the
+    /// original tree is returned. The result may reference fewer terminals
+    /// than the original.
+    pub fn minimize_with(&self, options: &MinimizeOptions) -> Self {
+        let terminals = self.terminals().into_iter().collect::<Vec<_>>();
+        let witness = terminals[0];
+        let exact = terminals.len() <= options.exact_threshold.min(MAX_EXACT_TERMINALS);
+
+        let mut best = self.clone();
+        for negated in [false, true] {
+            let cover = match exact.then(|| exact_cover(self, &terminals, negated)) {
+                Some(Some(cover)) => cover,
+                // the heuristic is also the fallback of the exact algorithm
+                _ => match heuristic_cover(self, &terminals, negated, best.node_count()) {
+                    Some(cover) => cover,
+                    None => continue,
+                },
+            };
+            let candidate = lower(&cover, negated, witness);
+            if candidate.node_count() < best.node_count() {
+                best = candidate;
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::random_tree;
+    use std::str::FromStr;
+
+    fn minimized(source: &str) -> String {
+        LogicTree::from_str(source).unwrap().minimize().to_string()
+    }
+
+    #[test]
+    fn redundant_expressions() {
+        assert_eq!(minimized("0 AND (0 OR 1)"), "0");
+        assert_eq!(minimized("0 OR (0 AND 1) OR (1 AND 0)"), "0");
+        assert_eq!(minimized("0 AND 1 OR (0 AND (1 NAND 1))"), "0");
+        assert_eq!(
+            minimized("0 AND 1 OR (0 AND 2) OR (1 AND 2 AND 0)"),
+            "0 AND 1 OR (0 AND 2)"
+        );
+        assert_eq!(minimized("(0 NAND 0) AND (1 NAND 1)"), "0 NOR 1");
+        // already minimal
+        assert_eq!(minimized("0 XOR 1"), "0 XOR 1");
+        assert_eq!(minimized("0 NAND 1"), "0 NAND 1");
+        // constants
+        assert_eq!(minimized("0 AND 1 AND (0 XOR 0) OR (1 XOR 1)"), "0 XOR 0");
+    }
+
+    #[test]
+    fn exact_is_equivalent() {
+        for seed in 0..200 {
+            let tree = random_tree(seed, 5, 14);
+            let minimized = tree.minimize();
+            assert!(
+                minimized.equivalent(&tree).holds(),
+                "{} => {}",
+                tree,
+                minimized
+            );
+            assert!(minimized.node_count() <= tree.node_count());
+        }
+
+        // terminals beyond the sixth span whole words of the truth table
+        let options = MinimizeOptions {
+            exact_threshold: usize::MAX,
+        };
+        for seed in 0..20 {
+            let tree = random_tree(seed, 9, 16);
+            let minimized = tree.minimize_with(&options);
+            assert!(minimized.equivalent(&tree).holds());
+            assert!(minimized.node_count() <= tree.node_count());
+        }
+        // falls back to the heuristic above the cap
+        let parity = (1..16).fold(LogicTree::terminal(0), |tree, id| tree.xor(id.into()));
+        assert!(parity.minimize_with(&options).equivalent(&parity).holds());
+    }
+
+    #[test]
+    fn heuristic_is_equivalent() {
+        let options = MinimizeOptions { exact_threshold: 0 };
+        for seed in 0..100 {
+            let tree = random_tree(seed, 6, 10);
+            let minimized = tree.minimize_with(&options);
+            assert!(
+                minimized.equivalent(&tree).holds(),
+                "{} => {}",
+                tree,
+                minimized
+            );
+            assert!(minimized.node_count() <= tree.node_count());
+        }
+
+        let tree = LogicTree::from_str("0 AND 1 OR (0 AND 2) OR (1 AND 2 AND 0)").unwrap();
+        assert_eq!(
+            tree.minimize_with(&options).to_string(),
+            "0 AND 1 OR (0 AND 2)"
+        );
+    }
+
+    #[test]
+    fn exact_and_heuristic_agree_on_redundancy() {
+        // 24 terminals, far beyond the exact threshold
+        let tree = (0..12).fold(LogicTree::terminal(100), |tree, id| {
+            let pair = LogicTree::terminal(2 * id).and(LogicTree::terminal(2 * id + 1));
+            tree.or(pair.clone().and(LogicTree::terminal(100)))
+        });
+        assert_eq!(tree.minimize().to_string(), "100");
+    }
+
+    #[test]
+    fn heuristic_scales() {
+        // (0 OR 1) AND (2 OR 3) AND ... has 2^11 terms in its sum of products
+        let and_of_ors = (1..11).fold(LogicTree::terminal(0).or(1.into()), |tree, i| {
+            tree.and(LogicTree::terminal(2 * i).or((2 * i + 1).into()))
+        });
+        // XOR chains have exponentially many terms in their sum of products
+        let with_xor = (0..22).fold(LogicTree::terminal(0).and(1.into()), |tree, id| {
+            tree.xor(id.into())
+        });
+        assert_eq!(and_of_ors.terminals().len(), 22);
+        assert_eq!(with_xor.terminals().len(), 22);
+        let mut trees = vec![and_of_ors, with_xor];
+        trees.extend((0..5).map(|seed| random_tree(seed, 20, 31)));
+        trees.extend((0..5).map(|seed| random_tree(seed, 16, 16)));
+
+        let options = MinimizeOptions { exact_threshold: 0 };
+        for tree in trees {
+            let minimized = tree.minimize_with(&options);
+            assert!(
+                minimized.equivalent(&tree).holds(),
+                "{} => {}",
+                tree,
+                minimized
+            );
+            assert!(minimized.node_count() <= tree.node_count());
+        }
+    }
+}
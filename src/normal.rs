@@ -0,0 +1,520 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A possibly negated variable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Literal<V = TerminalId> {
+    pub variable: V,
+    pub negated: bool,
+}
+
+impl<V> Literal<V> {
+    pub fn positive(variable: V) -> Self {
+        Self {
+            variable,
+            negated: false,
+        }
+    }
+
+    pub fn negative(variable: V) -> Self {
+        Self {
+            variable,
+            negated: true,
+        }
+    }
+}
+
+impl<V> std::ops::Not for Literal<V> {
+    type Output = Self;
+    fn not(self) -> Self {
+        Self {
+            variable: self.variable,
+            negated: !self.negated,
+        }
+    }
+}
+
+impl<V: std::fmt::Display> std::fmt::Display for Literal<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.negated {
+            write!(f, "!{}", self.variable)
+        } else {
+            write!(f, "{}", self.variable)
+        }
+    }
+}
+
+/// Variables of an equisatisfiable CNF, see [`LogicTree::to_tseitin_cnf`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Variable {
+    Terminal(TerminalId),
+    /// Stands for the output of a gate.
+    Auxiliary(u32),
+}
+
+impl std::fmt::Display for Variable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Terminal(id) => write!(f, "{}", id),
+            Self::Auxiliary(n) => write!(f, "_{}", n),
+        }
+    }
+}
+
+/// Conjunction of clauses, each clause being a disjunction of literals.
+///
+/// A formula without clauses is always `true`, a formula containing an empty
+/// clause is always `false`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cnf<V = TerminalId> {
+    pub clauses: Vec<Vec<Literal<V>>>,
+}
+
+/// Disjunction of terms, each term being a conjunction of literals.
+///
+/// A formula without terms is always `false`, a formula containing an empty
+/// term is always `true`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dnf<V = TerminalId> {
+    pub terms: Vec<Vec<Literal<V>>>,
+}
+
+impl<V: Copy + Eq + Hash> Cnf<V> {
+    pub fn evaluate(&self, values: &HashMap<V, bool>) -> Result<bool, String> {
+        for clause in &self.clauses {
+            let mut satisfied = false;
+            for literal in clause {
+                satisfied |= literal_value(literal, values)?;
+            }
+            if !satisfied {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns the value of the formula if it is syntactically constant, i.e.
+    /// it has no clauses or an empty clause.
+    pub fn constant(&self) -> Option<bool> {
+        constant(&self.clauses, false)
+    }
+}
+
+impl<V: Copy + Eq + Hash> Dnf<V> {
+    pub fn evaluate(&self, values: &HashMap<V, bool>) -> Result<bool, String> {
+        for term in &self.terms {
+            let mut satisfied = true;
+            for literal in term {
+                satisfied &= literal_value(literal, values)?;
+            }
+            if satisfied {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns the value of the formula if it is syntactically constant, i.e.
+    /// it has no terms or an empty term.
+    pub fn constant(&self) -> Option<bool> {
+        constant(&self.terms, true)
+    }
+}
+
+impl Cnf {
+    /// Converts the formula back into a tree, or returns `None` if it is
+    /// constant.
+    pub fn to_tree(&self) -> Option<LogicTree> {
+        nested_to_tree(&self.clauses, Gate::And, Gate::Or)
+    }
+}
+
+impl Dnf {
+    /// Converts the formula back into a tree, or returns `None` if it is
+    /// constant.
+    pub fn to_tree(&self) -> Option<LogicTree> {
+        nested_to_tree(&self.terms, Gate::Or, Gate::And)
+    }
+}
+
+impl<V: std::fmt::Display> std::fmt::Display for Cnf<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        display_nested(f, &self.clauses, "AND", "OR", "TRUE", "FALSE")
+    }
+}
+
+impl<V: std::fmt::Display> std::fmt::Display for Dnf<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        display_nested(f, &self.terms, "OR", "AND", "FALSE", "TRUE")
+    }
+}
+
+fn literal_value<V: Eq + Hash>(
+    literal: &Literal<V>,
+    values: &HashMap<V, bool>,
+) -> Result<bool, String> {
+    values
+        .get(&literal.variable)
+        .map(|value| *value != literal.negated)
+        .ok_or_else(|| "Invalid terminals map".to_string())
+}
+
+fn constant<V>(groups: &[Vec<Literal<V>>], empty_group: bool) -> Option<bool> {
+    if groups.is_empty() {
+        Some(!empty_group)
+    } else if groups.iter().any(Vec::is_empty) {
+        Some(empty_group)
+    } else {
+        None
+    }
+}
+
+fn nested_to_tree(groups: &[Vec<Literal>], outer: Gate, inner: Gate) -> Option<LogicTree> {
+    if groups.is_empty() || groups.iter().any(Vec::is_empty) {
+        return None;
+    }
+    groups
+        .iter()
+        .map(|group| {
+            group
+                .iter()
+                .map(|literal| {
+                    let terminal = LogicTree::terminal(literal.variable);
+                    if literal.negated {
+                        terminal.not()
+                    } else {
+                        terminal
+                    }
+                })
+                .reduce(|left, right| LogicTree::gate(inner, left, right))
+                .unwrap()
+        })
+        .reduce(|left, right| LogicTree::gate(outer, left, right))
+}
+
+fn display_nested<V: std::fmt::Display>(
+    f: &mut std::fmt::Formatter<'_>,
+    groups: &[Vec<Literal<V>>],
+    outer: &str,
+    inner: &str,
+    empty_outer: &str,
+    empty_inner: &str,
+) -> std::fmt::Result {
+    if groups.is_empty() {
+        return write!(f, "{}", empty_outer);
+    }
+    let rendered = groups
+        .iter()
+        .map(|group| {
+            if group.is_empty() {
+                empty_inner.to_string()
+            } else {
+                let literals = group.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+                format!("({})", literals.join(&format!(" {} ", inner)))
+            }
+        })
+        .collect::<Vec<_>>();
+    write!(f, "{}", rendered.join(&format!(" {} ", outer)))
+}
+
+/// Negation normal form, where only terminals may be negated.
+enum Nnf {
+    Literal(Literal),
+    And(Box<Nnf>, Box<Nnf>),
+    Or(Box<Nnf>, Box<Nnf>),
+}
+
+impl Nnf {
+    fn new(tree: &LogicTree, negated: bool) -> Self {
+        match tree {
+            LogicTree::Terminal(id) => Self::Literal(Literal {
+                variable: *id,
+                negated,
+            }),
+            LogicTree::Gate { gate, left, right } => match (gate, negated) {
+                (Gate::And, false) | (Gate::Nand, true) => {
+                    Self::and(Self::new(left, false), Self::new(right, false))
+                }
+                (Gate::Or, false) | (Gate::Nor, true) => {
+                    Self::or(Self::new(left, false), Self::new(right, false))
+                }
+                (Gate::Nand, false) | (Gate::And, true) => {
+                    Self::or(Self::new(left, true), Self::new(right, true))
+                }
+                (Gate::Nor, false) | (Gate::Or, true) => {
+                    Self::and(Self::new(left, true), Self::new(right, true))
+                }
+                // a XOR b = (a OR b) AND (!a OR !b)
+                // !(a XOR b) = (a OR !b) AND (!a OR b)
+                (Gate::Xor, _) => Self::and(
+                    Self::or(Self::new(left, false), Self::new(right, negated)),
+                    Self::or(Self::new(left, true), Self::new(right, !negated)),
+                ),
+            },
+        }
+    }
+
+    fn and(left: Self, right: Self) -> Self {
+        Self::And(Box::new(left), Box::new(right))
+    }
+
+    fn or(left: Self, right: Self) -> Self {
+        Self::Or(Box::new(left), Box::new(right))
+    }
+
+    /// Distributes the formula into groups of literals. With `conjunctive` set
+    /// the groups are clauses, otherwise they are terms.
+    fn distribute(&self, conjunctive: bool) -> Vec<Vec<Literal>> {
+        match self {
+            Self::Literal(literal) => vec![vec![*literal]],
+            Self::And(left, right) | Self::Or(left, right) => {
+                let left = left.distribute(conjunctive);
+                let right = right.distribute(conjunctive);
+                // for a CNF conjunctions concatenate clauses while
+                // disjunctions take their cross product, and vice versa
+                if matches!(self, Self::And(..)) == conjunctive {
+                    reduce_groups(left.into_iter().chain(right).collect())
+                } else {
+                    let product = left
+                        .iter()
+                        .flat_map(|l| right.iter().map(move |r| [l.as_slice(), r].concat()))
+                        .collect();
+                    reduce_groups(product)
+                }
+            }
+        }
+    }
+}
+
+/// Sorts and deduplicates the literals of each group, drops groups holding
+/// complementary literals and groups that are supersets of another group.
+fn reduce_groups(groups: Vec<Vec<Literal>>) -> Vec<Vec<Literal>> {
+    let mut groups = groups
+        .into_iter()
+        .filter_map(|mut group| {
+            group.sort_unstable();
+            group.dedup();
+            let complementary = group
+                .windows(2)
+                .any(|pair| pair[0].variable == pair[1].variable);
+            (!complementary).then_some(group)
+        })
+        .collect::<Vec<_>>();
+    groups.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    groups.dedup();
+
+    let mut reduced: Vec<Vec<Literal>> = Vec::with_capacity(groups.len());
+    for group in groups {
+        let subsumed = reduced.iter().any(|kept| {
+            kept.iter()
+                .all(|literal| group.binary_search(literal).is_ok())
+        });
+        if !subsumed {
+            reduced.push(group);
+        }
+    }
+    reduced
+}
+
+/// Tseitin encoding of trees where every gate output is represented by a
+/// fresh auxiliary variable.
+#[derive(Default)]
+pub(crate) struct Tseitin {
+    auxiliaries: u32,
+    pub clauses: Vec<Vec<Literal<Variable>>>,
+}
+
+impl Tseitin {
+    pub fn auxiliaries(&self) -> u32 {
+        self.auxiliaries
+    }
+
+    /// Adds the defining clauses of every gate and returns the literal
+    /// standing for the output of the tree.
+    pub fn encode(&mut self, tree: &LogicTree) -> Literal<Variable> {
+        match tree {
+            LogicTree::Terminal(id) => Literal::positive(Variable::Terminal(*id)),
+            LogicTree::Gate { gate, left, right } => {
+                let a = self.encode(left);
+                let b = self.encode(right);
+                let o = Literal::positive(Variable::Auxiliary(self.auxiliaries));
+                self.auxiliaries += 1;
+                let clauses = match gate {
+                    Gate::And => vec![vec![!o, a], vec![!o, b], vec![o, !a, !b]],
+                    Gate::Or => vec![vec![o, !a], vec![o, !b], vec![!o, a, b]],
+                    Gate::Nand => vec![vec![o, a], vec![o, b], vec![!o, !a, !b]],
+                    Gate::Nor => vec![vec![!o, !a], vec![!o, !b], vec![o, a, b]],
+                    Gate::Xor => vec![
+                        vec![!o, a, b],
+                        vec![!o, !a, !b],
+                        vec![o, !a, b],
+                        vec![o, a, !b],
+                    ],
+                };
+                self.clauses.extend(clauses);
+                o
+            }
+        }
+    }
+}
+
+impl LogicTree {
+    /// Converts the tree into an equivalent conjunctive normal form.
+    ///
+    /// The conversion distributes disjunctions over conjunctions, so the
+    /// number of clauses may grow exponentially. Use
+    /// [`LogicTree::to_tseitin_cnf`] when only satisfiability matters.
+    pub fn to_cnf(&self) -> Cnf {
+        Cnf {
+            clauses: Nnf::new(self, false).distribute(true),
+        }
+    }
+
+    /// Converts the tree into an equivalent disjunctive normal form.
+    ///
+    /// The number of terms may grow exponentially with the size of the tree.
+    pub fn to_dnf(&self) -> Dnf {
+        Dnf {
+            terms: Nnf::new(self, false).distribute(false),
+        }
+    }
+
+    /// Converts the tree into an equisatisfiable conjunctive normal form that
+    /// grows linearly with the size of the tree.
+    ///
+    /// Every gate output gets an auxiliary variable. The formula is satisfied
+    /// exactly by the assignments where the auxiliaries hold the gate outputs
+    /// and the tree evaluates to `true`.
+    pub fn to_tseitin_cnf(&self) -> Cnf<Variable> {
+        let mut tseitin = Tseitin::default();
+        let root = tseitin.encode(self);
+        let mut clauses = tseitin.clauses;
+        clauses.push(vec![root]);
+        Cnf { clauses }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::str::FromStr;
+
+    #[test]
+    fn cnf() {
+        let tree = LogicTree::from_str("0 AND (1 OR 2)").unwrap();
+        let cnf = tree.to_cnf();
+        assert_eq!(
+            cnf.clauses,
+            [
+                vec![Literal::positive(0)],
+                vec![Literal::positive(1), Literal::positive(2)],
+            ]
+        );
+        assert_eq!(cnf.to_string(), "(0) AND (1 OR 2)");
+
+        let tree = LogicTree::from_str("0 XOR 1").unwrap();
+        assert_eq!(tree.to_cnf().to_string(), "(0 OR 1) AND (!0 OR !1)");
+        let tree = LogicTree::from_str("0 NAND 1").unwrap();
+        assert_eq!(tree.to_cnf().to_string(), "(!0 OR !1)");
+        let tree = LogicTree::from_str("0 NOR 1").unwrap();
+        assert_eq!(tree.to_cnf().to_string(), "(!0) AND (!1)");
+    }
+
+    #[test]
+    fn dnf() {
+        let tree = LogicTree::from_str("0 AND (1 OR 2)").unwrap();
+        let dnf = tree.to_dnf();
+        assert_eq!(dnf.to_string(), "(0 AND 1) OR (0 AND 2)");
+
+        let tree = LogicTree::from_str("0 XOR 1").unwrap();
+        assert_eq!(tree.to_dnf().to_string(), "(0 AND !1) OR (!0 AND 1)");
+        let tree = LogicTree::from_str("0 NOR 1").unwrap();
+        assert_eq!(tree.to_dnf().to_string(), "(!0 AND !1)");
+        // absorbed terms are dropped
+        let tree = LogicTree::from_str("0 OR (0 AND 1)").unwrap();
+        assert_eq!(tree.to_dnf().to_string(), "(0)");
+    }
+
+    #[test]
+    fn constants() {
+        let contradiction = LogicTree::from_str("0 XOR 0").unwrap();
+        // complementary unit clauses are not resolved
+        assert_eq!(contradiction.to_cnf().to_string(), "(0) AND (!0)");
+        assert_eq!(contradiction.to_cnf().constant(), None);
+        assert_eq!(contradiction.to_dnf().constant(), Some(false));
+        assert_eq!(contradiction.to_dnf().to_string(), "FALSE");
+        assert!(contradiction.to_dnf().to_tree().is_none());
+
+        let tautology = LogicTree::from_str("0 OR (0 NAND 0)").unwrap();
+        assert_eq!(tautology.to_cnf().constant(), Some(true));
+        assert_eq!(tautology.to_cnf().to_string(), "TRUE");
+        assert_eq!(tautology.to_dnf().to_string(), "(0) OR (!0)");
+
+        let tree = LogicTree::from_str("0 AND 1").unwrap();
+        assert_eq!(tree.to_cnf().constant(), None);
+    }
+
+    #[test]
+    fn normal_forms_are_equivalent() {
+        for seed in 0..200 {
+            let tree = random_tree(seed, 5, 10);
+            let cnf = tree.to_cnf();
+            let dnf = tree.to_dnf();
+            for assignment in assignments(&tree.terminals()) {
+                let expected = tree.evaluate(&assignment).unwrap();
+                assert_eq!(cnf.evaluate(&assignment).unwrap(), expected, "{}", tree);
+                assert_eq!(dnf.evaluate(&assignment).unwrap(), expected, "{}", tree);
+            }
+            if let Some(rebuilt) = cnf.to_tree() {
+                assert!(rebuilt.equivalent(&tree).holds());
+            }
+            if let Some(rebuilt) = dnf.to_tree() {
+                assert!(rebuilt.equivalent(&tree).holds());
+            }
+        }
+    }
+
+    #[test]
+    fn tseitin_is_equisatisfiable() {
+        let tree = LogicTree::from_str("0 AND 1 OR ((0 NAND 2) XOR 3)").unwrap();
+        let cnf = tree.to_tseitin_cnf();
+        // one auxiliary per gate, 3 or 4 clauses per gate and the root
+        assert_eq!(cnf.clauses.len(), 3 * 3 + 4 + 1);
+
+        for seed in 0..100 {
+            let tree = random_tree(seed, 4, 8);
+            let cnf = tree.to_tseitin_cnf();
+            let auxiliaries = cnf
+                .clauses
+                .iter()
+                .flatten()
+                .filter_map(|literal| match literal.variable {
+                    Variable::Auxiliary(n) => Some(n),
+                    Variable::Terminal(_) => None,
+                })
+                .max()
+                .map_or(0, |n| n + 1);
+            // for every terminal assignment the tree is true iff some
+            // auxiliary assignment satisfies the formula
+            for assignment in assignments(&tree.terminals()) {
+                let satisfiable = (0..1_u64 << auxiliaries).any(|bits| {
+                    let values = assignment
+                        .iter()
+                        .map(|(id, value)| (Variable::Terminal(*id), *value))
+                        .chain(
+                            (0..auxiliaries)
+                                .map(|n| (Variable::Auxiliary(n), bits & (1 << n) != 0)),
+                        )
+                        .collect();
+                    cnf.evaluate(&values).unwrap()
+                });
+                assert_eq!(satisfiable, tree.evaluate(&assignment).unwrap(), "{}", tree);
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::token::LogicTree;
+use crate::visit::{walk_tree_mut, VisitorMut};
+use crate::TerminalId;
+use thiserror::Error;
+
+use std::collections::HashMap;
+
+#[derive(Error, Debug)]
+pub enum RemapError {
+    #[error("terminal {0} has no mapping")]
+    UnmappedTerminal(TerminalId),
+    #[error("terminal {0} does not occur in the expression")]
+    MissingTerminal(TerminalId),
+}
+
+struct Remapper<'a> {
+    mapping: &'a HashMap<TerminalId, TerminalId>,
+}
+
+impl VisitorMut for Remapper<'_> {
+    fn visit_terminal_mut(&mut self, id: &mut TerminalId) {
+        if let Some(new_id) = self.mapping.get(id) {
+            *id = *new_id;
+        }
+    }
+}
+
+struct Substituter<'a> {
+    id: TerminalId,
+    subtree: &'a LogicTree,
+    replaced: usize,
+}
+
+impl VisitorMut for Substituter<'_> {
+    fn visit_tree_mut(&mut self, tree: &mut LogicTree) {
+        match tree {
+            LogicTree::Terminal(id) if *id == self.id => {
+                *tree = self.subtree.clone();
+                self.replaced += 1;
+            }
+            _ => walk_tree_mut(self, tree),
+        }
+    }
+}
+
+impl LogicTree {
+    /// Renumbers terminals according to `mapping`.
+    ///
+    /// Terminals without an entry in `mapping` are left untouched, use
+    /// [`LogicTree::try_remap_terminals`] to reject them instead.
+    pub fn remap_terminals(&mut self, mapping: &HashMap<TerminalId, TerminalId>) {
+        self.accept_mut(&mut Remapper { mapping })
+    }
+
+    /// Renumbers terminals according to `mapping`, failing without modifying
+    /// the tree if any referenced terminal is missing from it.
+    pub fn try_remap_terminals(
+        &mut self,
+        mapping: &HashMap<TerminalId, TerminalId>,
+    ) -> Result<(), RemapError> {
+        if let Some(id) = self
+            .terminals()
+            .into_iter()
+            .find(|id| !mapping.contains_key(id))
+        {
+            return Err(RemapError::UnmappedTerminal(id));
+        }
+        self.remap_terminals(mapping);
+        Ok(())
+    }
+
+    /// Replaces every occurrence of terminal `id` with `subtree` and returns
+    /// the number of replaced occurrences.
+    ///
+    /// Terminals within the inserted subtree are not substituted again.
+    pub fn substitute(&mut self, id: TerminalId, subtree: &LogicTree) -> usize {
+        let mut substituter = Substituter {
+            id,
+            subtree,
+            replaced: 0,
+        };
+        self.accept_mut(&mut substituter);
+        substituter.replaced
+    }
+
+    /// Same as [`LogicTree::substitute`] but fails if `id` does not occur in
+    /// the tree.
+    pub fn try_substitute(
+        &mut self,
+        id: TerminalId,
+        subtree: &LogicTree,
+    ) -> Result<usize, RemapError> {
+        match self.substitute(id, subtree) {
+            0 => Err(RemapError::MissingTerminal(id)),
+            replaced => Ok(replaced),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn remap() {
+        let mut tree = LogicTree::from_str("0 AND (1 OR 2) XOR 0").unwrap();
+        let mapping = [(0, 5), (2, 1), (1, 2)].into_iter().collect();
+        tree.remap_terminals(&mapping);
+        assert_eq!(tree.to_string(), "5 AND (2 OR 1) XOR 5");
+
+        let mapping = [(5, 0)].into_iter().collect();
+        tree.remap_terminals(&mapping);
+        assert_eq!(tree.to_string(), "0 AND (2 OR 1) XOR 0");
+    }
+
+    #[test]
+    fn checked_remap() {
+        let mut tree = LogicTree::from_str("0 AND (1 OR 2)").unwrap();
+        let mapping = [(0, 3), (2, 4)].into_iter().collect();
+        match tree.try_remap_terminals(&mapping) {
+            Err(RemapError::UnmappedTerminal(1)) => {}
+            _ => panic!("should fail on terminal 1"),
+        }
+        assert_eq!(tree.to_string(), "0 AND (1 OR 2)");
+
+        let mapping = [(0, 3), (1, 1), (2, 4)].into_iter().collect();
+        tree.try_remap_terminals(&mapping).unwrap();
+        assert_eq!(tree.to_string(), "3 AND (1 OR 4)");
+    }
+
+    #[test]
+    fn substitute() {
+        let mut tree = LogicTree::from_str("0 AND 1 OR 0").unwrap();
+        let subtree = LogicTree::from_str("0 XOR 2").unwrap();
+        assert_eq!(tree.substitute(0, &subtree), 2);
+        assert_eq!(tree.to_string(), "0 XOR 2 AND 1 OR (0 XOR 2)");
+        assert_eq!(tree.substitute(7, &subtree), 0);
+    }
+
+    #[test]
+    fn checked_substitute() {
+        let mut tree = LogicTree::from_str("3").unwrap();
+        let subtree = LogicTree::from_str("1 NOR 2").unwrap();
+        match tree.try_substitute(0, &subtree) {
+            Err(RemapError::MissingTerminal(0)) => {}
+            _ => panic!("should fail on terminal 0"),
+        }
+        assert_eq!(tree.try_substitute(3, &subtree).unwrap(), 1);
+        assert_eq!(tree.to_string(), "1 NOR 2");
+    }
+}
@@ -0,0 +1,444 @@
+use crate::normal::{Literal, Tseitin, Variable};
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::collections::HashMap;
+
+/// A literal of the solver, encoded as `variable << 1 | negated`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Lit(u32);
+
+impl Lit {
+    pub fn new(var: usize, negated: bool) -> Self {
+        Self((var as u32) << 1 | negated as u32)
+    }
+
+    pub fn var(self) -> usize {
+        (self.0 >> 1) as usize
+    }
+
+    pub fn negated(self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl std::ops::Not for Lit {
+    type Output = Self;
+    fn not(self) -> Self {
+        Self(self.0 ^ 1)
+    }
+}
+
+/// Conflict driven clause learning solver with two watched literals, 1-UIP
+/// learning, non-chronological backjumping and activity based branching.
+pub(crate) struct Solver {
+    clauses: Vec<Vec<Lit>>,
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    activity: Vec<f64>,
+    phases: Vec<bool>,
+    trail: Vec<Lit>,
+    trail_limits: Vec<usize>,
+    propagated: usize,
+    increment: f64,
+    unsatisfiable: bool,
+}
+
+impl Solver {
+    pub fn new(variables: usize) -> Self {
+        Self {
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); variables * 2],
+            values: vec![None; variables],
+            levels: vec![0; variables],
+            reasons: vec![None; variables],
+            activity: vec![0.0; variables],
+            phases: vec![false; variables],
+            trail: Vec::new(),
+            trail_limits: Vec::new(),
+            propagated: 0,
+            increment: 1.0,
+            unsatisfiable: false,
+        }
+    }
+
+    pub fn add_clause(&mut self, literals: &[Lit]) {
+        if self.unsatisfiable {
+            return;
+        }
+        let mut clause = literals.to_vec();
+        clause.sort_unstable_by_key(|lit| lit.0);
+        clause.dedup();
+        if clause.windows(2).any(|pair| pair[0] == !pair[1]) {
+            // tautological clause
+            return;
+        }
+        // clauses are only added before solving, so every assignment is on
+        // the root level
+        clause.retain(|lit| self.value(*lit) != Some(false));
+        if clause.iter().any(|lit| self.value(*lit) == Some(true)) {
+            return;
+        }
+        match clause.len() {
+            0 => self.unsatisfiable = true,
+            1 => {
+                self.assign(clause[0], None);
+                if self.propagate().is_some() {
+                    self.unsatisfiable = true;
+                }
+            }
+            _ => {
+                self.attach(clause);
+            }
+        }
+    }
+
+    /// Returns the value of each variable if the clauses are satisfiable.
+    pub fn solve(mut self) -> Option<Vec<bool>> {
+        if self.unsatisfiable {
+            return None;
+        }
+        loop {
+            if let Some(conflict) = self.propagate() {
+                if self.trail_limits.is_empty() {
+                    return None;
+                }
+                let (learnt, level) = self.analyze(conflict);
+                self.backjump(level);
+                let asserting = learnt[0];
+                if learnt.len() == 1 {
+                    self.assign(asserting, None);
+                } else {
+                    let index = self.attach(learnt);
+                    self.assign(asserting, Some(index));
+                }
+                self.increment *= 1.05;
+            } else if let Some(var) = self.pick_branch() {
+                self.trail_limits.push(self.trail.len());
+                self.assign(Lit::new(var, !self.phases[var]), None);
+            } else {
+                return Some(self.values.iter().map(|v| v.unwrap_or(false)).collect());
+            }
+        }
+    }
+
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.values[lit.var()].map(|value| value != lit.negated())
+    }
+
+    fn level(&self) -> usize {
+        self.trail_limits.len()
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        let var = lit.var();
+        self.values[var] = Some(!lit.negated());
+        self.levels[var] = self.level();
+        self.reasons[var] = reason;
+        self.trail.push(lit);
+    }
+
+    fn attach(&mut self, clause: Vec<Lit>) -> usize {
+        let index = self.clauses.len();
+        self.watches[(!clause[0]).index()].push(index);
+        self.watches[(!clause[1]).index()].push(index);
+        self.clauses.push(clause);
+        index
+    }
+
+    /// Propagates unit clauses and returns the index of a conflicting clause.
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let lit = self.trail[self.propagated];
+            self.propagated += 1;
+            let false_lit = !lit;
+            let watching = std::mem::take(&mut self.watches[lit.index()]);
+            let mut kept = Vec::with_capacity(watching.len());
+            let mut conflict = None;
+            let mut watchers = watching.into_iter();
+
+            for index in watchers.by_ref() {
+                let clause = &mut self.clauses[index];
+                if clause[0] == false_lit {
+                    clause.swap(0, 1);
+                }
+                let first = clause[0];
+                if self.values[first.var()].map(|value| value != first.negated()) == Some(true) {
+                    kept.push(index);
+                    continue;
+                }
+                let replacement = (2..clause.len()).find(|&k| {
+                    let candidate = clause[k];
+                    self.values[candidate.var()].map(|value| value != candidate.negated())
+                        != Some(false)
+                });
+                if let Some(k) = replacement {
+                    clause.swap(1, k);
+                    let watched = !clause[1];
+                    self.watches[watched.index()].push(index);
+                    continue;
+                }
+                kept.push(index);
+                if self.value(first) == Some(false) {
+                    conflict = Some(index);
+                    break;
+                }
+                self.assign(first, Some(index));
+            }
+
+            kept.extend(watchers);
+            self.watches[lit.index()].extend(kept);
+            if conflict.is_some() {
+                self.propagated = self.trail.len();
+                return conflict;
+            }
+        }
+        None
+    }
+
+    /// Derives the first unique implication point clause from a conflict and
+    /// returns it with the level to backjump to. The asserting literal is
+    /// placed first and a literal of the backjump level second.
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, usize) {
+        let mut seen = vec![false; self.values.len()];
+        let mut learnt = vec![Lit(0)];
+        let mut pending = 0;
+        let mut clause = conflict;
+        let mut index = self.trail.len();
+        let mut pivot: Option<Lit> = None;
+
+        loop {
+            for k in 0..self.clauses[clause].len() {
+                let lit = self.clauses[clause][k];
+                if Some(lit) == pivot {
+                    continue;
+                }
+                let var = lit.var();
+                if seen[var] || self.levels[var] == 0 {
+                    continue;
+                }
+                seen[var] = true;
+                self.bump(var);
+                if self.levels[var] == self.level() {
+                    pending += 1;
+                } else {
+                    learnt.push(lit);
+                }
+            }
+            loop {
+                index -= 1;
+                if seen[self.trail[index].var()] {
+                    break;
+                }
+            }
+            let lit = self.trail[index];
+            seen[lit.var()] = false;
+            pending -= 1;
+            if pending == 0 {
+                learnt[0] = !lit;
+                break;
+            }
+            pivot = Some(lit);
+            clause = self.reasons[lit.var()].expect("implied literal without a reason");
+        }
+
+        let mut level = 0;
+        if learnt.len() > 1 {
+            let (position, max_level) = (1..learnt.len())
+                .map(|k| (k, self.levels[learnt[k].var()]))
+                .max_by_key(|(_, level)| *level)
+                .unwrap();
+            learnt.swap(1, position);
+            level = max_level;
+        }
+        (learnt, level)
+    }
+
+    fn backjump(&mut self, level: usize) {
+        if self.level() <= level {
+            return;
+        }
+        let limit = self.trail_limits[level];
+        for lit in self.trail.drain(limit..) {
+            let var = lit.var();
+            self.phases[var] = !lit.negated();
+            self.values[var] = None;
+            self.reasons[var] = None;
+        }
+        self.trail_limits.truncate(level);
+        self.propagated = self.trail.len();
+    }
+
+    fn bump(&mut self, var: usize) {
+        self.activity[var] += self.increment;
+        if self.activity[var] > 1e100 {
+            self.activity
+                .iter_mut()
+                .for_each(|activity| *activity *= 1e-100);
+            self.increment *= 1e-100;
+        }
+    }
+
+    fn pick_branch(&self) -> Option<usize> {
+        (0..self.values.len())
+            .filter(|&var| self.values[var].is_none())
+            .max_by(|&a, &b| {
+                self.activity[a]
+                    .total_cmp(&self.activity[b])
+                    .then(b.cmp(&a))
+            })
+    }
+}
+
+/// Searches for an assignment under which every tree evaluates to the paired
+/// boolean and the terminals in `fixed` keep their value.
+///
+/// The returned assignment covers every terminal of the trees and of `fixed`.
+pub(crate) fn solve(
+    constraints: &[(&LogicTree, bool)],
+    fixed: &HashMap<TerminalId, bool>,
+) -> Option<HashMap<TerminalId, bool>> {
+    let mut tseitin = Tseitin::default();
+    let mut units = Vec::new();
+    for (tree, output) in constraints {
+        let root = tseitin.encode(tree);
+        units.push(vec![if *output { root } else { !root }]);
+    }
+    for (id, value) in fixed {
+        let literal = Literal::positive(Variable::Terminal(*id));
+        units.push(vec![if *value { literal } else { !literal }]);
+    }
+
+    // auxiliaries come first, terminals are numbered after them
+    let auxiliaries = tseitin.auxiliaries() as usize;
+    let mut terminals = HashMap::new();
+    let clauses = tseitin
+        .clauses
+        .into_iter()
+        .chain(units)
+        .map(|clause| {
+            clause
+                .into_iter()
+                .map(|literal| {
+                    let var = match literal.variable {
+                        Variable::Auxiliary(n) => n as usize,
+                        Variable::Terminal(id) => {
+                            let next = auxiliaries + terminals.len();
+                            *terminals.entry(id).or_insert(next)
+                        }
+                    };
+                    Lit::new(var, literal.negated)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut solver = Solver::new(auxiliaries + terminals.len());
+    for clause in &clauses {
+        solver.add_clause(clause);
+    }
+    let values = solver.solve()?;
+    Some(
+        terminals
+            .into_iter()
+            .map(|(id, var)| (id, values[var]))
+            .collect(),
+    )
+}
+
+impl LogicTree {
+    /// Returns `true` if some assignment of the terminals makes the tree
+    /// evaluate to `true`.
+    pub fn is_satisfiable(&self) -> bool {
+        self.find_satisfying_assignment().is_some()
+    }
+
+    /// Returns `true` if the tree evaluates to `true` under every assignment.
+    pub fn is_tautology(&self) -> bool {
+        solve(&[(self, false)], &HashMap::new()).is_none()
+    }
+
+    /// Returns an assignment of every referenced terminal under which the tree
+    /// evaluates to `true`.
+    pub fn find_satisfying_assignment(&self) -> Option<HashMap<TerminalId, bool>> {
+        solve(&[(self, true)], &HashMap::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::str::FromStr;
+
+    #[test]
+    fn small_expressions() {
+        let tree = LogicTree::from_str("0 AND (0 XOR 0)").unwrap();
+        assert!(!tree.is_satisfiable());
+        assert!(!tree.is_tautology());
+        assert!(tree.find_satisfying_assignment().is_none());
+
+        let tree = LogicTree::from_str("0 OR (0 NAND 0)").unwrap();
+        assert!(tree.is_satisfiable());
+        assert!(tree.is_tautology());
+
+        let tree = LogicTree::from_str("0 AND 1 OR ((0 NAND 2) OR 3)").unwrap();
+        assert!(tree.is_satisfiable());
+        assert!(!tree.is_tautology());
+        let assignment = tree.find_satisfying_assignment().unwrap();
+        assert_eq!(assignment.len(), 4);
+        assert!(tree.evaluate(&assignment).unwrap());
+    }
+
+    #[test]
+    fn agrees_with_brute_force() {
+        for seed in 0..300 {
+            let tree = if seed % 2 == 0 {
+                random_tree(seed, 5, 12)
+            } else {
+                random_tree(seed, 10, 40)
+            };
+            let terminals = tree.terminals();
+            let outputs = assignments(&terminals)
+                .map(|assignment| tree.evaluate(&assignment).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                tree.is_satisfiable(),
+                outputs.iter().any(|x| *x),
+                "{}",
+                tree
+            );
+            assert_eq!(tree.is_tautology(), outputs.iter().all(|x| *x), "{}", tree);
+            if let Some(assignment) = tree.find_satisfying_assignment() {
+                assert!(tree.evaluate(&assignment).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn many_terminals() {
+        // (0 XOR 1 XOR ... XOR 79) with every second terminal forced to true
+        let parity = (1..80).fold(LogicTree::terminal(0), |tree, id| {
+            tree.xor(LogicTree::terminal(id))
+        });
+        let forced = (0..80).step_by(2).fold(LogicTree::terminal(0), |tree, id| {
+            tree.and(LogicTree::terminal(id))
+        });
+        let tree = parity.clone().and(forced);
+        let assignment = tree.find_satisfying_assignment().unwrap();
+        assert_eq!(assignment.len(), 80);
+        assert!(tree.evaluate(&assignment).unwrap());
+        assert!(!tree.is_tautology());
+
+        // a formula contradicting itself is never satisfied
+        let contradiction = parity.clone().xor(parity.clone());
+        assert!(!contradiction.is_satisfiable());
+        assert!(parity.clone().or(parity.not()).is_tautology());
+    }
+}
@@ -0,0 +1,324 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+/// Rewrite rules applied by [`LogicTree::simplify`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// `x AND x = x`, `x OR x = x`
+    Idempotence,
+    /// `x AND (x OR y) = x`, `x OR (x AND y) = x`
+    Absorption,
+    /// `NOT NOT x = x`
+    DoubleNegation,
+    /// `x XOR x = false`
+    XorSelf,
+    /// `x AND NOT x = false`, `x OR NOT x = true`, `x XOR NOT x = true`
+    Complement,
+    /// Gates with a constant input are replaced by a constant or by their
+    /// other input.
+    ConstantPropagation,
+    /// `NOT x AND NOT y = x NOR y`, `NOT x OR NOT y = x NAND y`
+    DeMorgan,
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            Self::Idempotence => "idempotence: x AND x = x, x OR x = x",
+            Self::Absorption => "absorption: x AND (x OR y) = x, x OR (x AND y) = x",
+            Self::DoubleNegation => "double negation: NOT NOT x = x",
+            Self::XorSelf => "self exclusion: x XOR x = false",
+            Self::Complement => {
+                "complement: x AND NOT x = false, x OR NOT x = true, x XOR NOT x = true"
+            }
+            Self::ConstantPropagation => "constant propagation",
+            Self::DeMorgan => "De Morgan: NOT x AND NOT y = x NOR y, NOT x OR NOT y = x NAND y",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// The result of [`LogicTree::simplify`].
+#[derive(Clone, Debug)]
+pub struct Simplified {
+    pub tree: LogicTree,
+    /// Every rule application in the order they fired.
+    pub rules: Vec<Rule>,
+}
+
+/// Expression with explicit negations and constants, NAND and NOR are
+/// represented as negated AND and OR.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Expr {
+    Constant(bool),
+    Terminal(TerminalId),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn new(tree: &LogicTree) -> Self {
+        match tree {
+            LogicTree::Terminal(id) => Self::Terminal(*id),
+            LogicTree::Gate { gate, left, right } => {
+                let left = Box::new(Self::new(left));
+                let right = Box::new(Self::new(right));
+                match gate {
+                    Gate::And => Self::And(left, right),
+                    Gate::Or => Self::Or(left, right),
+                    Gate::Nand => Self::Not(Box::new(Self::And(left, right))),
+                    Gate::Nor => Self::Not(Box::new(Self::Or(left, right))),
+                    Gate::Xor => Self::Xor(left, right),
+                }
+            }
+        }
+    }
+
+    fn is_negation_of(&self, other: &Self) -> bool {
+        matches!(self, Self::Not(inner) if inner.as_ref() == other)
+    }
+
+    /// Checks whether the expression is `operand OR y` or `y OR operand`, or
+    /// the same with AND when `or` is unset.
+    fn has_operand(&self, operand: &Self, or: bool) -> bool {
+        match self {
+            Self::Or(a, b) if or => a.as_ref() == operand || b.as_ref() == operand,
+            Self::And(a, b) if !or => a.as_ref() == operand || b.as_ref() == operand,
+            _ => false,
+        }
+    }
+
+    fn into_tree(self, witness: TerminalId) -> LogicTree {
+        match self {
+            Self::Constant(value) => LogicTree::constant(value, witness),
+            Self::Terminal(id) => LogicTree::terminal(id),
+            // negations are folded into the tree so that no operand is copied
+            Self::Not(inner) => match *inner {
+                Self::Constant(value) => LogicTree::constant(!value, witness),
+                Self::Terminal(id) => LogicTree::terminal(id).not(),
+                Self::Not(inner) => inner.into_tree(witness),
+                Self::And(a, b) => a.into_tree(witness).nand(b.into_tree(witness)),
+                Self::Or(a, b) => a.into_tree(witness).nor(b.into_tree(witness)),
+                Self::Xor(a, b) => a.into_tree(witness).xor(Self::Not(b).into_tree(witness)),
+            },
+            Self::And(a, b) => a.into_tree(witness).and(b.into_tree(witness)),
+            Self::Or(a, b) => a.into_tree(witness).or(b.into_tree(witness)),
+            Self::Xor(a, b) => a.into_tree(witness).xor(b.into_tree(witness)),
+        }
+    }
+}
+
+/// Simplifies the children first, then rewrites the node itself until no rule
+/// applies.
+fn rewrite(expr: Expr, rules: &mut Vec<Rule>) -> Expr {
+    let expr = match expr {
+        Expr::Not(inner) => Expr::Not(Box::new(rewrite(*inner, rules))),
+        Expr::And(a, b) => Expr::And(Box::new(rewrite(*a, rules)), Box::new(rewrite(*b, rules))),
+        Expr::Or(a, b) => Expr::Or(Box::new(rewrite(*a, rules)), Box::new(rewrite(*b, rules))),
+        Expr::Xor(a, b) => Expr::Xor(Box::new(rewrite(*a, rules)), Box::new(rewrite(*b, rules))),
+        leaf => leaf,
+    };
+    match rewrite_node(expr, rules) {
+        Ok(rewritten) => rewrite(rewritten, rules),
+        Err(unchanged) => unchanged,
+    }
+}
+
+/// Applies a single rule to a node with simplified children, or returns the
+/// node unchanged.
+fn rewrite_node(expr: Expr, rules: &mut Vec<Rule>) -> Result<Expr, Expr> {
+    let or = matches!(expr, Expr::Or(..));
+    let (rule, rewritten) = match expr {
+        Expr::Not(inner) => match *inner {
+            Expr::Constant(value) => (Rule::ConstantPropagation, Expr::Constant(!value)),
+            Expr::Not(x) => (Rule::DoubleNegation, *x),
+            inner => return Err(Expr::Not(Box::new(inner))),
+        },
+        Expr::And(a, b) | Expr::Or(a, b) => {
+            // the value a constant input forces on the gate
+            let dominant = or;
+            match (*a, *b) {
+                (Expr::Constant(value), x) | (x, Expr::Constant(value)) => {
+                    if value == dominant {
+                        (Rule::ConstantPropagation, Expr::Constant(dominant))
+                    } else {
+                        (Rule::ConstantPropagation, x)
+                    }
+                }
+                (a, b) if a == b => (Rule::Idempotence, a),
+                (a, b) if a.is_negation_of(&b) || b.is_negation_of(&a) => {
+                    (Rule::Complement, Expr::Constant(dominant))
+                }
+                (a, b) if b.has_operand(&a, !or) => (Rule::Absorption, a),
+                (a, b) if a.has_operand(&b, !or) => (Rule::Absorption, b),
+                (Expr::Not(a), Expr::Not(b)) => {
+                    let inner = if or { Expr::And(a, b) } else { Expr::Or(a, b) };
+                    (Rule::DeMorgan, Expr::Not(Box::new(inner)))
+                }
+                (a, b) if or => return Err(Expr::Or(Box::new(a), Box::new(b))),
+                (a, b) => return Err(Expr::And(Box::new(a), Box::new(b))),
+            }
+        }
+        Expr::Xor(a, b) => match (*a, *b) {
+            (Expr::Constant(false), x) | (x, Expr::Constant(false)) => {
+                (Rule::ConstantPropagation, x)
+            }
+            (Expr::Constant(true), x) | (x, Expr::Constant(true)) => {
+                (Rule::ConstantPropagation, Expr::Not(Box::new(x)))
+            }
+            (a, b) if a == b => (Rule::XorSelf, Expr::Constant(false)),
+            (a, b) if a.is_negation_of(&b) || b.is_negation_of(&a) => {
+                (Rule::Complement, Expr::Constant(true))
+            }
+            (a, b) => return Err(Expr::Xor(Box::new(a), Box::new(b))),
+        },
+        leaf => return Err(leaf),
+    };
+    rules.push(rule);
+    Ok(rewritten)
+}
+
+impl LogicTree {
+    /// Applies cheap algebraic rewrite rules and reports which of them fired.
+    ///
+    /// Unlike [`LogicTree::minimize`] this never restructures the expression
+    /// beyond the listed [`Rule`]s, so the result stays recognizable. A
+    /// constant result is expressed through the smallest referenced terminal,
+    /// see [`LogicTree::constant`].
+    pub fn simplify(&self) -> Simplified {
+        let witness = self.terminals().into_iter().next().unwrap_or_default();
+        let mut rules = Vec::new();
+        let expr = rewrite(Expr::new(self), &mut rules);
+        Simplified {
+            tree: expr.into_tree(witness),
+            rules,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::random_tree;
+    use std::str::FromStr;
+
+    fn simplified(source: &str) -> (String, Vec<Rule>) {
+        let simplified = LogicTree::from_str(source).unwrap().simplify();
+        (simplified.tree.to_string(), simplified.rules)
+    }
+
+    #[test]
+    fn rules() {
+        let (_, rules) = simplified("0 XOR (0 NAND 0)");
+        assert!(rules.contains(&Rule::Complement));
+        assert!(Rule::Complement.to_string().contains("x XOR NOT x = true"));
+        assert_eq!(
+            simplified("0 AND 0 OR 1"),
+            ("0 OR 1".to_string(), vec![Rule::Idempotence])
+        );
+        assert_eq!(
+            simplified("0 AND (0 OR 1)"),
+            ("0".to_string(), vec![Rule::Absorption])
+        );
+        assert_eq!(
+            simplified("(1 AND 0) OR 0"),
+            ("0".to_string(), vec![Rule::Absorption])
+        );
+        assert_eq!(
+            simplified("(0 NAND 0) NAND (0 NAND 0)"),
+            (
+                "0".to_string(),
+                vec![
+                    Rule::Idempotence,
+                    Rule::Idempotence,
+                    Rule::Idempotence,
+                    Rule::DoubleNegation
+                ]
+            )
+        );
+        assert_eq!(
+            simplified("(0 NAND 0) AND (1 NAND 1)"),
+            (
+                "0 NOR 1".to_string(),
+                vec![Rule::Idempotence, Rule::Idempotence, Rule::DeMorgan]
+            )
+        );
+        assert_eq!(
+            simplified("(0 AND 1) XOR (0 AND 1) OR 2"),
+            (
+                "2".to_string(),
+                vec![Rule::XorSelf, Rule::ConstantPropagation]
+            )
+        );
+        assert_eq!(
+            simplified("0 AND (1 XOR 1)"),
+            (
+                "0 XOR 0".to_string(),
+                vec![Rule::XorSelf, Rule::ConstantPropagation]
+            )
+        );
+        assert_eq!(
+            simplified("(0 NOR 0) OR 0 AND 1"),
+            (
+                "1".to_string(),
+                vec![
+                    Rule::Idempotence,
+                    Rule::Complement,
+                    Rule::ConstantPropagation
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn untouched() {
+        for source in ["0", "0 AND 1 OR 2", "0 NAND 1", "0 NOR (1 XOR 2)"] {
+            let (tree, rules) = simplified(source);
+            assert_eq!(tree, source);
+            assert!(rules.is_empty());
+        }
+    }
+
+    #[test]
+    fn rule_display() {
+        assert_eq!(
+            Rule::DoubleNegation.to_string(),
+            "double negation: NOT NOT x = x"
+        );
+    }
+
+    #[test]
+    fn simplified_is_equivalent() {
+        for seed in 0..300 {
+            let tree = random_tree(seed, 3, 12);
+            let simplified = tree.simplify();
+            assert!(
+                simplified.tree.equivalent(&tree).holds(),
+                "{} => {}",
+                tree,
+                simplified.tree
+            );
+        }
+    }
+
+    #[test]
+    fn never_grows() {
+        for seed in 0..1000 {
+            let tree = random_tree(seed, 4, 16);
+            let simplified = tree.simplify().tree;
+            assert!(
+                simplified.node_count() <= tree.node_count(),
+                "{} => {}",
+                tree,
+                simplified
+            );
+        }
+
+        // negated XOR produced by constant propagation
+        let (tree, _) = simplified("0 AND 1 XOR (3 NOR 4) XOR (2 OR (2 NAND 2))");
+        assert_eq!(tree, "0 AND 1 XOR (3 OR 4)");
+    }
+}
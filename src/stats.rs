@@ -0,0 +1,107 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+impl LogicTree {
+    /// Returns every terminal referenced by the tree, ordered and deduplicated.
+    pub fn terminals(&self) -> BTreeSet<TerminalId> {
+        self.terminal_counts().into_keys().collect()
+    }
+
+    /// Returns how many times each terminal occurs in the tree.
+    pub fn terminal_counts(&self) -> BTreeMap<TerminalId, usize> {
+        let mut counts = BTreeMap::new();
+        for node in self.preorder() {
+            if let Self::Terminal(id) = node {
+                *counts.entry(*id).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Length of the longest root-to-leaf path, where a single terminal has
+    /// depth 1.
+    pub fn depth(&self) -> usize {
+        match self {
+            Self::Terminal(_) => 1,
+            Self::Gate { left, right, .. } => 1 + left.depth().max(right.depth()),
+        }
+    }
+
+    /// Total number of nodes (gates and terminals) in the tree.
+    pub fn node_count(&self) -> usize {
+        self.preorder().count()
+    }
+
+    /// Returns how many times each gate kind occurs in the tree.
+    pub fn gate_histogram(&self) -> HashMap<Gate, usize> {
+        let mut histogram = HashMap::new();
+        for node in self.preorder() {
+            if let Self::Gate { gate, .. } = node {
+                *histogram.entry(*gate).or_insert(0) += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Returns the requirement ids that never appear in the logic.
+    ///
+    /// For a `requirements` vector of length `n`, pass `0..n`.
+    pub fn unused_terminals<I>(&self, requirements: I) -> BTreeSet<TerminalId>
+    where
+        I: IntoIterator<Item = TerminalId>,
+    {
+        let referenced = self.terminals();
+        requirements
+            .into_iter()
+            .filter(|id| !referenced.contains(id))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn referenced_terminals() {
+        let tree = LogicTree::from_str("3 AND (1 OR 3) XOR (0 NAND 1)").unwrap();
+        assert_eq!(
+            tree.terminals().into_iter().collect::<Vec<_>>(),
+            vec![0, 1, 3]
+        );
+        let counts = tree.terminal_counts();
+        assert_eq!(counts.get(&0), Some(&1));
+        assert_eq!(counts.get(&1), Some(&2));
+        assert_eq!(counts.get(&2), None);
+        assert_eq!(counts.get(&3), Some(&2));
+    }
+
+    #[test]
+    fn tree_shape() {
+        let tree = LogicTree::from_str("7").unwrap();
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.node_count(), 1);
+        assert!(tree.gate_histogram().is_empty());
+
+        let tree = LogicTree::from_str("0 AND 1 OR ((0 NAND 2) OR 3)").unwrap();
+        assert_eq!(tree.depth(), 4);
+        assert_eq!(tree.node_count(), 9);
+        let histogram = tree.gate_histogram();
+        assert_eq!(histogram.get(&Gate::And), Some(&1));
+        assert_eq!(histogram.get(&Gate::Or), Some(&2));
+        assert_eq!(histogram.get(&Gate::Nand), Some(&1));
+        assert_eq!(histogram.get(&Gate::Xor), None);
+    }
+
+    #[test]
+    fn unused_requirements() {
+        let tree = LogicTree::from_str("0 AND (2 OR 4)").unwrap();
+        let unused = tree.unused_terminals(0..6);
+        assert_eq!(unused.into_iter().collect::<Vec<_>>(), vec![1, 3, 5]);
+        assert!(tree.unused_terminals([0, 2, 4]).is_empty());
+    }
+}
@@ -0,0 +1,377 @@
+use crate::bdd::{Bdd, BddNode};
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+
+#[derive(Clone, Debug)]
+pub struct SuggestOptions {
+    /// Cost of changing the value of a terminal.
+    pub costs: HashMap<TerminalId, u64>,
+    /// Cost of terminals missing from `costs`.
+    pub default_cost: u64,
+    /// Maximum number of returned suggestions.
+    pub limit: usize,
+}
+
+impl Default for SuggestOptions {
+    fn default() -> Self {
+        Self {
+            costs: HashMap::new(),
+            default_cost: 1,
+            limit: 3,
+        }
+    }
+}
+
+/// A set of terminal changes that makes a tree pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The new value of each terminal that has to change.
+    pub flips: BTreeMap<TerminalId, bool>,
+    pub cost: u64,
+}
+
+impl std::fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let flips = self
+            .flips
+            .iter()
+            .map(|(id, value)| format!("{} = {}", id, value))
+            .collect::<Vec<_>>();
+        write!(f, "{} (cost {})", flips.join(", "), self.cost)
+    }
+}
+
+/// Cost and number of flips of a path, which ranks suggestions.
+type Key = (u64, usize);
+
+struct Search<'a> {
+    bdd: &'a Bdd,
+    terminals: &'a HashMap<TerminalId, bool>,
+    options: &'a SuggestOptions,
+    cheapest: HashMap<BddNode, Option<Key>>,
+}
+
+impl Search<'_> {
+    fn cost(&self, id: TerminalId) -> u64 {
+        *self
+            .options
+            .costs
+            .get(&id)
+            .unwrap_or(&self.options.default_cost)
+    }
+
+    /// Returns the kept and the flipped child of an inner node, with the new
+    /// value of its variable when flipped.
+    fn children(&self, node: BddNode) -> Option<(TerminalId, bool, BddNode, BddNode)> {
+        let (id, low, high) = self.bdd.branch(node)?;
+        let current = self.terminals[&id];
+        let (kept, flipped) = if current { (high, low) } else { (low, high) };
+        Some((id, !current, kept, flipped))
+    }
+
+    /// Key of the cheapest path from `node` to the `true` leaf.
+    fn cheapest(&mut self, node: BddNode) -> Option<Key> {
+        let (id, _, kept, flipped) = match self.children(node) {
+            Some(children) => children,
+            None => return (node == BddNode::TRUE).then_some((0, 0)),
+        };
+        if let Some(key) = self.cheapest.get(&node) {
+            return *key;
+        }
+        let cost = self.cost(id);
+        let kept = self.cheapest(kept);
+        let flipped = self
+            .cheapest(flipped)
+            .map(|(total, flips)| (total.saturating_add(cost), flips + 1));
+        let key = match (kept, flipped) {
+            (Some(kept), Some(flipped)) => Some(kept.min(flipped)),
+            (kept, flipped) => kept.or(flipped),
+        };
+        self.cheapest.insert(node, key);
+        key
+    }
+
+    /// Enumerates paths to the `true` leaf best first and keeps those which
+    /// are not a superset of an earlier one.
+    ///
+    /// Paths are ranked by cost, number of flips and then by the flips
+    /// themselves. Variables are ordered by id, so the flips of a partial path
+    /// are a prefix of those of any completion, and the rank of a partial path
+    /// never exceeds that of the paths it leads to. Suggestions are therefore
+    /// found in rank order, and a strict subset of a path always comes first.
+    fn suggestions(&mut self) -> Vec<Suggestion> {
+        let root = self.bdd.root();
+        let mut queue = BinaryHeap::new();
+        if let Some(bound) = self.cheapest(root) {
+            queue.push(Reverse((bound, BTreeMap::new(), 0, root)));
+        }
+        let mut found: Vec<Suggestion> = Vec::new();
+        while found.len() < self.options.limit {
+            let Some(Reverse((_, flips, cost, node))) = queue.pop() else {
+                break;
+            };
+            let path = Suggestion { flips, cost };
+            let superset = found.iter().any(|suggestion| {
+                suggestion
+                    .flips
+                    .iter()
+                    .all(|(id, value)| path.flips.get(id) == Some(value))
+            });
+            if superset {
+                continue;
+            }
+            let (id, flipped_value, kept, flipped) = match self.children(node) {
+                Some(children) => children,
+                None => {
+                    found.push(path);
+                    continue;
+                }
+            };
+            let cost = self.cost(id);
+            for (child, flip) in [(kept, false), (flipped, true)] {
+                let Some((rest, rest_flips)) = self.cheapest(child) else {
+                    continue;
+                };
+                let mut next = path.clone();
+                if flip {
+                    next.flips.insert(id, flipped_value);
+                    next.cost = next.cost.saturating_add(cost);
+                }
+                let bound = (
+                    next.cost.saturating_add(rest),
+                    next.flips.len() + rest_flips,
+                );
+                queue.push(Reverse((bound, next.flips, next.cost, child)));
+            }
+        }
+        found
+    }
+}
+
+impl LogicTree {
+    /// Suggests the cheapest sets of terminal changes that make the tree
+    /// evaluate to `true`, ranked by cost. Ties are ranked by the number of
+    /// changes and then by the changed terminals in ascending order.
+    ///
+    /// No suggestion is a superset of another. A passing assignment yields a
+    /// single empty suggestion, while a tree that can never pass yields none.
+    pub fn suggestions(
+        &self,
+        terminals: &HashMap<TerminalId, bool>,
+        options: &SuggestOptions,
+    ) -> Result<Vec<Suggestion>, String> {
+        // fails early on missing terminals
+        self.evaluate(terminals)?;
+        let bdd = Bdd::from_tree(self);
+        let mut search = Search {
+            bdd: &bdd,
+            terminals,
+            options,
+            cheapest: HashMap::new(),
+        };
+        Ok(search.suggestions())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+    use std::str::FromStr;
+
+    fn flips(suggestions: &[Suggestion]) -> Vec<Vec<(TerminalId, bool)>> {
+        suggestions
+            .iter()
+            .map(|suggestion| suggestion.flips.clone().into_iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn cheapest_paths() {
+        let tree = LogicTree::from_str("0 AND (2 OR 3)").unwrap();
+        let terminals = [(0, true), (2, false), (3, false)].into_iter().collect();
+        let suggestions = tree
+            .suggestions(&terminals, &SuggestOptions::default())
+            .unwrap();
+        assert_eq!(flips(&suggestions), [vec![(2, true)], vec![(3, true)]]);
+        assert_eq!(suggestions[0].to_string(), "2 = true (cost 1)");
+
+        let options = SuggestOptions {
+            costs: [(2, 5)].into_iter().collect(),
+            ..Default::default()
+        };
+        let suggestions = tree.suggestions(&terminals, &options).unwrap();
+        assert_eq!(flips(&suggestions), [vec![(3, true)], vec![(2, true)]]);
+        assert_eq!(suggestions[1].cost, 5);
+    }
+
+    #[test]
+    fn flips_to_false() {
+        let tree = LogicTree::from_str("0 XOR 1").unwrap();
+        let terminals = [(0, true), (1, true)].into_iter().collect();
+        let options = SuggestOptions {
+            limit: 5,
+            ..Default::default()
+        };
+        let suggestions = tree.suggestions(&terminals, &options).unwrap();
+        assert_eq!(flips(&suggestions), [vec![(0, false)], vec![(1, false)]]);
+    }
+
+    #[test]
+    fn limits() {
+        let tree = LogicTree::from_str("0 OR 1 OR 2 OR 3").unwrap();
+        let terminals = (0..4).map(|id| (id, false)).collect();
+        let options = SuggestOptions {
+            limit: 2,
+            ..Default::default()
+        };
+        assert_eq!(tree.suggestions(&terminals, &options).unwrap().len(), 2);
+
+        let passing = (0..4).map(|id| (id, true)).collect();
+        let suggestions = tree.suggestions(&passing, &options).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert!(suggestions[0].flips.is_empty());
+
+        let tree = LogicTree::from_str("0 AND (0 XOR 0)").unwrap();
+        let terminals = [(0, false)].into_iter().collect();
+        assert!(tree.suggestions(&terminals, &options).unwrap().is_empty());
+        assert!(tree.suggestions(&HashMap::new(), &options).is_err());
+    }
+
+    #[test]
+    fn limited_ranking() {
+        let tree = LogicTree::from_str(
+            "3 XOR (2 OR (3 AND (0 OR 0 XOR 1) OR 4)) AND (0 OR (5 NAND 5 OR (1 OR 2)))",
+        )
+        .unwrap();
+        let terminals = [
+            (0, true),
+            (1, false),
+            (2, false),
+            (3, true),
+            (4, true),
+            (5, true),
+        ]
+        .into_iter()
+        .collect();
+        let options = SuggestOptions {
+            costs: [(0, 2), (1, 3), (2, 2), (3, 3), (4, 3), (5, 4)]
+                .into_iter()
+                .collect(),
+            default_cost: 1,
+            limit: 3,
+        };
+        let suggestions = tree.suggestions(&terminals, &options).unwrap();
+        let costs = suggestions.iter().map(|s| s.cost).collect::<Vec<_>>();
+        assert_eq!(costs, [3, 6, 9]);
+
+        // a limit only cuts the unlimited ranking
+        for seed in 0..300 {
+            let tree = random_tree(seed, 6, 14);
+            let ids = tree.terminals();
+            let current = assignments(&ids)
+                .nth(seed as usize % (1 << ids.len()))
+                .unwrap();
+            let mut options = SuggestOptions {
+                costs: ids
+                    .iter()
+                    .map(|id| (*id, (seed + u64::from(*id) * 7) % 4 + 1))
+                    .collect(),
+                default_cost: 1,
+                limit: usize::MAX,
+            };
+            let unlimited = tree.suggestions(&current, &options).unwrap();
+            for limit in 0..=unlimited.len() {
+                options.limit = limit;
+                let limited = tree.suggestions(&current, &options).unwrap();
+                assert_eq!(limited, unlimited[..limit], "{} limit {}", tree, limit);
+            }
+        }
+    }
+
+    #[test]
+    fn many_equal_paths() {
+        // every one of the 2^20 ways to pass costs the same
+        let source = (0..20)
+            .map(|i| format!("({} OR {})", 2 * i, 2 * i + 1))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let tree = LogicTree::from_str(&source).unwrap();
+        let terminals = (0..40).map(|id| (id, false)).collect();
+        let suggestions = tree
+            .suggestions(&terminals, &SuggestOptions::default())
+            .unwrap();
+        let evens = (0..18).map(|i| (2 * i, true));
+        let expected: [Vec<_>; 3] = [
+            evens.clone().chain([(36, true), (38, true)]).collect(),
+            evens.clone().chain([(36, true), (39, true)]).collect(),
+            evens
+                .take(17)
+                .chain([(34, true), (37, true), (38, true)])
+                .collect(),
+        ];
+        assert_eq!(flips(&suggestions), expected);
+        assert!(suggestions.iter().all(|suggestion| suggestion.cost == 20));
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let options = SuggestOptions {
+            costs: [(0, 3), (1, 2)].into_iter().collect(),
+            limit: usize::MAX,
+            ..Default::default()
+        };
+        for seed in 0..100 {
+            let tree = random_tree(seed, 5, 10);
+            let ids = tree.terminals();
+            let current = assignments(&ids)
+                .nth(seed as usize % (1 << ids.len()))
+                .unwrap();
+
+            // every minimal set of flips that makes the tree pass
+            let mut expected = Vec::new();
+            for target in assignments(&ids) {
+                if !tree.evaluate(&target).unwrap() {
+                    continue;
+                }
+                let flips = target
+                    .into_iter()
+                    .filter(|(id, value)| current[id] != *value)
+                    .collect::<BTreeMap<_, _>>();
+                expected.push(flips);
+            }
+            let minimal = expected
+                .iter()
+                .filter(|flips| {
+                    !expected.iter().any(|other| {
+                        other.len() < flips.len()
+                            && other.iter().all(|(k, v)| flips.get(k) == Some(v))
+                    })
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let suggestions = tree.suggestions(&current, &options).unwrap();
+            assert_eq!(suggestions.len(), minimal.len(), "{}", tree);
+            for suggestion in &suggestions {
+                assert!(minimal.contains(&suggestion.flips));
+                let mut flipped = current.clone();
+                flipped.extend(suggestion.flips.iter().map(|(k, v)| (*k, *v)));
+                assert!(tree.evaluate(&flipped).unwrap());
+                let cost = suggestion
+                    .flips
+                    .keys()
+                    .map(|id| options.costs.get(id).copied().unwrap_or(1))
+                    .sum::<u64>();
+                assert_eq!(suggestion.cost, cost);
+            }
+            let rank = |s: &Suggestion| (s.cost, s.flips.len(), s.flips.clone());
+            assert!(suggestions
+                .windows(2)
+                .all(|pair| rank(&pair[0]) < rank(&pair[1])));
+        }
+    }
+}
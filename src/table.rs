@@ -0,0 +1,270 @@
+use crate::token::LogicTree;
+use crate::TerminalId;
+use thiserror::Error;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Default upper bound on the number of terminals of a truth table, which has
+/// `2^n` rows for `n` terminals.
+pub const MAX_TRUTH_TABLE_TERMINALS: usize = 16;
+
+const OUTPUT_HEADER: &str = "result";
+
+#[derive(Error, Debug)]
+pub enum TruthTableError {
+    #[error("{count} terminals exceed the truth table limit of {limit}")]
+    TooManyTerminals { count: usize, limit: usize },
+}
+
+/// Truth table over the terminals referenced by a tree.
+///
+/// Rows are generated lazily, counting up in binary with the smallest terminal
+/// as the most significant bit.
+#[derive(Clone, Debug)]
+pub struct TruthTable<'a> {
+    tree: &'a LogicTree,
+    terminals: Vec<TerminalId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Row {
+    /// Terminal values in the order of [`TruthTable::terminals`].
+    pub inputs: Vec<bool>,
+    pub output: bool,
+}
+
+pub struct Rows<'a> {
+    table: &'a TruthTable<'a>,
+    next: u64,
+}
+
+impl Iterator for Rows<'_> {
+    type Item = Row;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.table.row_count() {
+            return None;
+        }
+        let width = self.table.terminals.len();
+        let inputs = (0..width)
+            .map(|i| self.next & (1 << (width - 1 - i)) != 0)
+            .collect::<Vec<_>>();
+        let assignment = self
+            .table
+            .terminals
+            .iter()
+            .copied()
+            .zip(inputs.iter().copied())
+            .collect::<HashMap<_, _>>();
+        let output = self
+            .table
+            .tree
+            .evaluate(&assignment)
+            .expect("every referenced terminal is assigned");
+        self.next += 1;
+        Some(Row { inputs, output })
+    }
+}
+
+impl<'a> TruthTable<'a> {
+    pub fn terminals(&self) -> &[TerminalId] {
+        &self.terminals
+    }
+
+    pub fn row_count(&self) -> u64 {
+        1 << self.terminals.len()
+    }
+
+    pub fn rows(&self) -> Rows<'_> {
+        Rows {
+            table: self,
+            next: 0,
+        }
+    }
+
+    /// Renders the table with aligned columns.
+    pub fn to_text(&self) -> String {
+        let headers = self.headers();
+        let widths = headers.iter().map(String::len).collect::<Vec<_>>();
+        let mut text = headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| {
+                if i + 1 == headers.len() {
+                    format!("| {}", header)
+                } else {
+                    header.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        text.push('\n');
+        for row in self.rows() {
+            for (value, width) in row.inputs.iter().zip(&widths) {
+                let _ = write!(text, "{:<width$} ", symbol(*value), width = width);
+            }
+            let _ = writeln!(text, "| {}", symbol(row.output));
+        }
+        text
+    }
+
+    /// Renders the table as a Markdown table.
+    pub fn to_markdown(&self) -> String {
+        let headers = self.headers();
+        let mut markdown = format!("| {} |\n", headers.join(" | "));
+        let _ = writeln!(markdown, "|{}", ":-:|".repeat(headers.len()));
+        for row in self.rows() {
+            let cells = row
+                .inputs
+                .iter()
+                .chain(std::iter::once(&row.output))
+                .map(|value| symbol(*value))
+                .collect::<Vec<_>>();
+            let _ = writeln!(markdown, "| {} |", cells.join(" | "));
+        }
+        markdown
+    }
+
+    /// Renders the table as comma separated values.
+    pub fn to_csv(&self) -> String {
+        let mut csv = self.headers().join(",");
+        csv.push('\n');
+        for row in self.rows() {
+            let cells = row
+                .inputs
+                .iter()
+                .chain(std::iter::once(&row.output))
+                .map(bool::to_string)
+                .collect::<Vec<_>>();
+            csv.push_str(&cells.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    fn headers(&self) -> Vec<String> {
+        self.terminals
+            .iter()
+            .map(TerminalId::to_string)
+            .chain(std::iter::once(OUTPUT_HEADER.to_string()))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for TruthTable<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_text())
+    }
+}
+
+fn symbol(value: bool) -> &'static str {
+    if value {
+        "T"
+    } else {
+        "F"
+    }
+}
+
+impl LogicTree {
+    /// Returns the truth table of the tree, failing if it references more than
+    /// [`MAX_TRUTH_TABLE_TERMINALS`] terminals.
+    pub fn truth_table(&self) -> Result<TruthTable<'_>, TruthTableError> {
+        self.truth_table_with_limit(MAX_TRUTH_TABLE_TERMINALS)
+    }
+
+    /// Returns the truth table of the tree, failing if it references more than
+    /// `limit` terminals.
+    ///
+    /// Rows are indexed by an `u64`, so the limit is capped at 63 terminals.
+    pub fn truth_table_with_limit(&self, limit: usize) -> Result<TruthTable<'_>, TruthTableError> {
+        let terminals = self.terminals().into_iter().collect::<Vec<_>>();
+        let limit = limit.min(63);
+        if terminals.len() > limit {
+            return Err(TruthTableError::TooManyTerminals {
+                count: terminals.len(),
+                limit,
+            });
+        }
+        Ok(TruthTable {
+            tree: self,
+            terminals,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn rows() {
+        let tree = LogicTree::from_str("1 AND 0 OR 1").unwrap();
+        let table = tree.truth_table().unwrap();
+        assert_eq!(table.terminals(), [0, 1]);
+        assert_eq!(table.row_count(), 4);
+        let rows = table.rows().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].inputs, [false, false]);
+        assert_eq!(rows[1].inputs, [false, true]);
+        assert_eq!(rows[2].inputs, [true, false]);
+        assert_eq!(rows[3].inputs, [true, true]);
+        let outputs = rows.iter().map(|row| row.output).collect::<Vec<_>>();
+        assert_eq!(outputs, [false, true, false, true]);
+    }
+
+    #[test]
+    fn terminal_limit() {
+        let tree = LogicTree::from_str("0 AND 1 AND 2").unwrap();
+        assert!(tree.truth_table_with_limit(3).is_ok());
+        match tree.truth_table_with_limit(2) {
+            Err(TruthTableError::TooManyTerminals { count: 3, limit: 2 }) => {}
+            _ => panic!("should exceed the limit"),
+        }
+        let tree = (0..=MAX_TRUTH_TABLE_TERMINALS as TerminalId)
+            .fold(LogicTree::terminal(0), |tree, id| tree.or(id.into()));
+        assert!(tree.truth_table().is_err());
+
+        let tree = (1..64).fold(LogicTree::terminal(0), |tree, id| tree.or(id.into()));
+        match tree.truth_table_with_limit(64) {
+            Err(TruthTableError::TooManyTerminals {
+                count: 64,
+                limit: 63,
+            }) => {}
+            _ => panic!("should exceed the capped limit"),
+        }
+    }
+
+    #[test]
+    fn render() {
+        let tree = LogicTree::from_str("0 XOR 10").unwrap();
+        let table = tree.truth_table().unwrap();
+        assert_eq!(
+            table.to_text(),
+            "0 10 | result\n\
+             F F  | F\n\
+             F T  | T\n\
+             T F  | T\n\
+             T T  | F\n"
+        );
+        assert_eq!(table.to_string(), table.to_text());
+        assert_eq!(
+            table.to_markdown(),
+            "| 0 | 10 | result |\n\
+             |:-:|:-:|:-:|\n\
+             | F | F | F |\n\
+             | F | T | T |\n\
+             | T | F | T |\n\
+             | T | T | F |\n"
+        );
+        assert_eq!(
+            table.to_csv(),
+            "0,10,result\n\
+             false,false,false\n\
+             false,true,true\n\
+             true,false,true\n\
+             true,true,false\n"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+//! Helpers shared by the unit tests.
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Deterministic pseudo random tree with `size` terminals drawn from
+/// `0..terminals`.
+pub fn random_tree(seed: u64, terminals: u32, size: usize) -> LogicTree {
+    let mut state = seed
+        .wrapping_mul(6364136223846793005)
+        .wrapping_add(1442695040888963407);
+    let mut next = move || {
+        // xorshift64*
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        (state.wrapping_mul(2685821657736338717) >> 32) as u32
+    };
+    let mut nodes = (0..size.max(1))
+        .map(|_| LogicTree::Terminal(next() % terminals))
+        .collect::<Vec<_>>();
+    while nodes.len() > 1 {
+        let left = nodes.swap_remove(next() as usize % nodes.len());
+        let right = nodes.swap_remove(next() as usize % nodes.len());
+        let gate = match next() % 5 {
+            0 => Gate::And,
+            1 => Gate::Or,
+            2 => Gate::Nand,
+            3 => Gate::Nor,
+            _ => Gate::Xor,
+        };
+        nodes.push(LogicTree::gate(gate, left, right));
+    }
+    nodes.pop().unwrap()
+}
+
+/// Iterates over every assignment of `terminals`.
+pub fn assignments(
+    terminals: &BTreeSet<TerminalId>,
+) -> impl Iterator<Item = HashMap<TerminalId, bool>> + '_ {
+    (0..1_u64 << terminals.len()).map(move |bits| {
+        terminals
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, bits & (1 << i) != 0))
+            .collect()
+    })
+}
@@ -0,0 +1,341 @@
+use super::parse::{parse_with, Builder};
+use super::{LogicTree, ParseError, Syntax};
+use crate::gate::Gate;
+use crate::visit::Fold;
+use crate::TerminalId;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+
+/// Index of a node within an [`ArenaTree`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ArenaNode(u32);
+
+/// Contents of a node of an [`ArenaTree`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArenaEntry {
+    Terminal(TerminalId),
+    Gate {
+        gate: Gate,
+        left: ArenaNode,
+        right: ArenaNode,
+    },
+}
+
+impl Builder for Vec<ArenaEntry> {
+    type Node = ArenaNode;
+
+    fn terminal(&mut self, id: TerminalId) -> ArenaNode {
+        self.push(ArenaEntry::Terminal(id));
+        ArenaNode(self.len() as u32 - 1)
+    }
+
+    fn gate(&mut self, gate: Gate, left: ArenaNode, right: ArenaNode) -> ArenaNode {
+        self.push(ArenaEntry::Gate { gate, left, right });
+        ArenaNode(self.len() as u32 - 1)
+    }
+}
+
+/// A [`LogicTree`] stored in a single vector, with children linked by index
+/// instead of boxed.
+///
+/// The parser builds it directly, saving an allocation per node. Nodes are
+/// stored children before parents and every node belongs to the tree, which
+/// makes evaluation a single pass over the vector.
+///
+/// [`Visitor`](crate::Visitor) and [`VisitorMut`](crate::VisitorMut) walk
+/// boxed nodes, so they need the tree from [`ArenaTree::to_tree`]. The arena
+/// itself is traversed with [`ArenaTree::fold`] and the node iterators.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ArenaTree {
+    nodes: Vec<ArenaEntry>,
+}
+
+impl ArenaTree {
+    pub fn new(source: &str) -> Result<Self, ParseError> {
+        Self::with_syntax(source, Syntax::Infix)
+    }
+
+    /// Parses the source like [`LogicTree::parse_any`].
+    pub fn parse_any(source: &str) -> Result<Self, ParseError> {
+        Self::with_syntax(source, Syntax::detect(source))
+    }
+
+    pub fn with_syntax(source: &str, syntax: Syntax) -> Result<Self, ParseError> {
+        let mut nodes = Vec::new();
+        parse_with(source, syntax, &mut nodes)?;
+        Ok(Self { nodes })
+    }
+
+    pub fn from_tree(tree: &LogicTree) -> Self {
+        let mut nodes = Vec::with_capacity(tree.node_count());
+        add(tree, &mut nodes);
+        Self { nodes }
+    }
+
+    /// Converts the arena into a boxed tree.
+    pub fn to_tree(&self) -> LogicTree {
+        self.node_to_tree(self.root())
+    }
+
+    pub fn node_to_tree(&self, node: ArenaNode) -> LogicTree {
+        match self.entry(node) {
+            ArenaEntry::Terminal(id) => LogicTree::Terminal(id),
+            ArenaEntry::Gate { gate, left, right } => {
+                LogicTree::gate(gate, self.node_to_tree(left), self.node_to_tree(right))
+            }
+        }
+    }
+
+    /// The root is the last node, as it is built after its children.
+    pub fn root(&self) -> ArenaNode {
+        ArenaNode(self.nodes.len() as u32 - 1)
+    }
+
+    pub fn entry(&self, node: ArenaNode) -> ArenaEntry {
+        self.nodes[node.0 as usize]
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns every terminal referenced by the tree, ordered and deduplicated.
+    pub fn terminals(&self) -> BTreeSet<TerminalId> {
+        self.terminal_counts().into_keys().collect()
+    }
+
+    /// Returns how many times each terminal occurs in the tree.
+    pub fn terminal_counts(&self) -> BTreeMap<TerminalId, usize> {
+        let mut counts = BTreeMap::new();
+        for entry in &self.nodes {
+            if let ArenaEntry::Terminal(id) = entry {
+                *counts.entry(*id).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Length of the longest root-to-leaf path, where a single terminal has
+    /// depth 1.
+    pub fn depth(&self) -> usize {
+        let mut depths: Vec<usize> = Vec::with_capacity(self.nodes.len());
+        for entry in &self.nodes {
+            let depth = match entry {
+                ArenaEntry::Terminal(_) => 1,
+                ArenaEntry::Gate { left, right, .. } => {
+                    1 + depths[left.0 as usize].max(depths[right.0 as usize])
+                }
+            };
+            depths.push(depth);
+        }
+        depths[self.root().0 as usize]
+    }
+
+    /// Returns how many times each gate kind occurs in the tree.
+    pub fn gate_histogram(&self) -> HashMap<Gate, usize> {
+        let mut histogram = HashMap::new();
+        for entry in &self.nodes {
+            if let ArenaEntry::Gate { gate, .. } = entry {
+                *histogram.entry(*gate).or_insert(0) += 1;
+            }
+        }
+        histogram
+    }
+
+    /// Returns the requirement ids that never appear in the logic.
+    ///
+    /// For a `requirements` vector of length `n`, pass `0..n`.
+    pub fn unused_terminals<I>(&self, requirements: I) -> BTreeSet<TerminalId>
+    where
+        I: IntoIterator<Item = TerminalId>,
+    {
+        let referenced = self.terminals();
+        requirements
+            .into_iter()
+            .filter(|id| !referenced.contains(id))
+            .collect()
+    }
+
+    pub fn evaluate(&self, terminals: &HashMap<TerminalId, bool>) -> Result<bool, String> {
+        // children come first, so their values are always computed already
+        let mut values = Vec::with_capacity(self.nodes.len());
+        for entry in &self.nodes {
+            let value = match entry {
+                ArenaEntry::Terminal(c) => *terminals
+                    .get(c)
+                    .ok_or_else(|| "Invalid terminals map".to_string())?,
+                ArenaEntry::Gate { gate, left, right } => {
+                    gate.apply(values[left.0 as usize], values[right.0 as usize])
+                }
+            };
+            values.push(value);
+        }
+        Ok(values[self.root().0 as usize])
+    }
+
+    /// Folds the tree like [`LogicTree::fold`], without recursion.
+    pub fn fold<F: Fold + ?Sized>(&self, folder: &mut F) -> F::Output {
+        let mut outputs = Vec::with_capacity(self.nodes.len());
+        for node in self.postorder() {
+            let output = match self.entry(node) {
+                ArenaEntry::Terminal(id) => folder.fold_terminal(id),
+                ArenaEntry::Gate { gate, .. } => {
+                    let right = outputs.pop().expect("right child is folded");
+                    let left = outputs.pop().expect("left child is folded");
+                    folder.fold_gate(gate, left, right)
+                }
+            };
+            outputs.push(output);
+        }
+        outputs.pop().expect("the root is folded")
+    }
+
+    /// Iterates over the nodes, parents before children.
+    pub fn preorder(&self) -> impl Iterator<Item = ArenaNode> + '_ {
+        let mut stack = vec![self.root()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            if let ArenaEntry::Gate { left, right, .. } = self.entry(node) {
+                stack.push(right);
+                stack.push(left);
+            }
+            Some(node)
+        })
+    }
+
+    /// Iterates over the nodes, children before parents.
+    pub fn postorder(&self) -> impl Iterator<Item = ArenaNode> + '_ {
+        // the flag marks whether the children of a node were already pushed
+        let mut stack = vec![(self.root(), false)];
+        std::iter::from_fn(move || loop {
+            let (node, expanded) = stack.pop()?;
+            match self.entry(node) {
+                ArenaEntry::Gate { left, right, .. } if !expanded => {
+                    stack.push((node, true));
+                    stack.push((right, false));
+                    stack.push((left, false));
+                }
+                _ => return Some(node),
+            }
+        })
+    }
+}
+
+fn add(tree: &LogicTree, nodes: &mut Vec<ArenaEntry>) -> ArenaNode {
+    match tree {
+        LogicTree::Terminal(id) => nodes.terminal(*id),
+        LogicTree::Gate { gate, left, right } => {
+            let left = add(left, nodes);
+            let right = add(right, nodes);
+            nodes.gate(*gate, left, right)
+        }
+    }
+}
+
+impl FromStr for ArenaTree {
+    type Err = ParseError;
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::new(input)
+    }
+}
+
+impl From<&LogicTree> for ArenaTree {
+    fn from(tree: &LogicTree) -> Self {
+        Self::from_tree(tree)
+    }
+}
+
+impl From<&ArenaTree> for LogicTree {
+    fn from(tree: &ArenaTree) -> Self {
+        tree.to_tree()
+    }
+}
+
+impl std::fmt::Display for ArenaTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_tree())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{assignments, random_tree};
+
+    #[test]
+    fn parse_into_arena() {
+        let arena = ArenaTree::from_str("0 AND (1 OR 2) XOR 0").unwrap();
+        assert_eq!(arena.node_count(), 7);
+        assert_eq!(arena.to_string(), "0 AND (1 OR 2) XOR 0");
+        assert_eq!(
+            arena.entry(arena.root()),
+            ArenaEntry::Gate {
+                gate: Gate::Xor,
+                left: ArenaNode(4),
+                right: ArenaNode(5),
+            }
+        );
+
+        let arena = ArenaTree::parse_any("(xor (and 0 (or 1 2)) 0)").unwrap();
+        assert_eq!(
+            arena.to_tree(),
+            LogicTree::from_str("0 AND (1 OR 2) XOR 0").unwrap()
+        );
+
+        assert!(ArenaTree::from_str("0 AND").is_err());
+        assert!(ArenaTree::parse_any("(and 0)").is_err());
+        assert!(ArenaTree::from_str("(and 0 1)").is_err());
+    }
+
+    #[test]
+    fn same_as_boxed_tree() {
+        for seed in 0..100 {
+            let tree = random_tree(seed, 5, 12);
+            let parsed = ArenaTree::from_str(&tree.to_string()).unwrap();
+            let converted = ArenaTree::from_tree(&tree);
+            assert_eq!(parsed, converted);
+            assert_eq!(parsed.to_tree(), tree);
+
+            for assignment in assignments(&tree.terminals()) {
+                assert_eq!(parsed.evaluate(&assignment), tree.evaluate(&assignment));
+            }
+
+            assert_eq!(parsed.terminal_counts(), tree.terminal_counts());
+            assert_eq!(parsed.depth(), tree.depth());
+            assert_eq!(parsed.node_count(), tree.node_count());
+            assert_eq!(parsed.gate_histogram(), tree.gate_histogram());
+            assert_eq!(parsed.unused_terminals(0..8), tree.unused_terminals(0..8));
+
+            let preorder = parsed
+                .preorder()
+                .map(|node| parsed.node_to_tree(node))
+                .collect::<Vec<_>>();
+            assert_eq!(preorder, tree.preorder().cloned().collect::<Vec<_>>());
+            let postorder = parsed
+                .postorder()
+                .map(|node| parsed.node_to_tree(node))
+                .collect::<Vec<_>>();
+            assert_eq!(postorder, tree.postorder().cloned().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn fold() {
+        struct Depth;
+        impl Fold for Depth {
+            type Output = usize;
+            fn fold_terminal(&mut self, _id: TerminalId) -> usize {
+                1
+            }
+            fn fold_gate(&mut self, _gate: Gate, left: usize, right: usize) -> usize {
+                1 + left.max(right)
+            }
+        }
+        for seed in 0..20 {
+            let tree = random_tree(seed, 5, 12);
+            let arena = ArenaTree::from_tree(&tree);
+            assert_eq!(arena.fold(&mut Depth), tree.fold(&mut Depth));
+        }
+    }
+}
@@ -1,10 +1,12 @@
+mod arena;
 mod parse;
 mod scan;
 
 use crate::gate::Gate;
 use crate::TerminalId;
-use parse::parse;
+pub use arena::{ArenaEntry, ArenaNode, ArenaTree};
 pub use parse::ParseError;
+use parse::{parse, parse_with_syntax};
 
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -18,7 +20,33 @@ pub enum Token {
     Gate(Gate),
 }
 
-#[derive(Clone, Debug)]
+/// Surface syntax of a textual gate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Syntax {
+    /// Upper case gates between their operands, such as `0 AND (1 OR 2)`,
+    /// evaluated left to right.
+    Infix,
+    /// Lower case gates leading a list of exactly two operands, such as
+    /// `(and 0 (or 1 2))`.
+    SExpression,
+}
+
+impl Syntax {
+    /// Guesses the syntax of the source from its first gate or terminal.
+    ///
+    /// A lone terminal reads the same in both syntaxes.
+    pub fn detect(source: &str) -> Self {
+        let first = source
+            .chars()
+            .find(|c| !c.is_ascii_whitespace() && *c != '(');
+        match first {
+            Some(c) if c.is_ascii_lowercase() => Self::SExpression,
+            _ => Self::Infix,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum LogicTree {
     Terminal(TerminalId),
     Gate {
@@ -33,26 +61,41 @@ impl LogicTree {
         parse(source)
     }
 
-    pub fn evaluate(&self, terminals: &HashMap<TerminalId, bool>) -> Result<bool, String> {
-        let eval;
+    /// Parses the source, detecting its syntax with [`Syntax::detect`].
+    ///
+    /// [`LogicTree::new`] and `FromStr` only accept the infix syntax.
+    pub fn parse_any(source: &str) -> Result<Self, ParseError> {
+        parse_with_syntax(source, Syntax::detect(source))
+    }
+
+    pub fn with_syntax(source: &str, syntax: Syntax) -> Result<Self, ParseError> {
+        parse_with_syntax(source, syntax)
+    }
+
+    /// Writes the tree as an S-expression, such as `(and 0 (or 1 2))`.
+    pub fn to_s_expression(&self) -> String {
         match self {
-            Self::Terminal(c) => {
-                eval = *terminals
-                    .get(c)
-                    .ok_or_else(|| "Invalid terminals map".to_string())?
-            }
+            Self::Terminal(c) => c.to_string(),
+            Self::Gate { gate, left, right } => format!(
+                "({} {} {})",
+                gate.to_string().to_ascii_lowercase(),
+                left.to_s_expression(),
+                right.to_s_expression()
+            ),
+        }
+    }
+
+    pub fn evaluate(&self, terminals: &HashMap<TerminalId, bool>) -> Result<bool, String> {
+        let eval = match self {
+            Self::Terminal(c) => *terminals
+                .get(c)
+                .ok_or_else(|| "Invalid terminals map".to_string())?,
             Self::Gate { gate, left, right } => {
                 let left_eval = left.evaluate(terminals)?;
                 let right_eval = right.evaluate(terminals)?;
-                match gate {
-                    Gate::And => eval = left_eval && right_eval,
-                    Gate::Or => eval = left_eval || right_eval,
-                    Gate::Nand => eval = !(left_eval && right_eval),
-                    Gate::Nor => eval = !(left_eval || right_eval),
-                    Gate::Xor => eval = left_eval ^ right_eval,
-                }
+                gate.apply(left_eval, right_eval)
             }
-        }
+        };
         Ok(eval)
     }
 }
@@ -64,6 +107,21 @@ impl FromStr for LogicTree {
     }
 }
 
+impl std::fmt::Display for LogicTree {
+    // gates are parsed left to right without precedence, so only right hand
+    // side gates need parentheses for the output to parse back into the same
+    // tree
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Terminal(c) => write!(f, "{}", c),
+            Self::Gate { gate, left, right } => match right.as_ref() {
+                Self::Terminal(_) => write!(f, "{} {} {}", left, gate, right),
+                Self::Gate { .. } => write!(f, "{} {} ({})", left, gate, right),
+            },
+        }
+    }
+}
+
 #[test]
 fn evaluate_tree() {
     let tree = LogicTree::from_str("0 AND 1 OR ((0 NAND 2) OR 3)").unwrap();
@@ -86,3 +144,44 @@ fn evaluate_tree() {
     *terminal = false;
     assert!(tree.evaluate(&terminals).unwrap());
 }
+
+#[test]
+fn display_tree() {
+    let tree = LogicTree::from_str("0 AND 1 OR ((0 NAND 2) OR 3)").unwrap();
+    assert_eq!(tree.to_string(), "0 AND 1 OR (0 NAND 2 OR 3)");
+    let tree = LogicTree::from_str("((((111))))").unwrap();
+    assert_eq!(tree.to_string(), "111");
+
+    for source in [
+        "0 AND 1 OR ((0 NAND 2) OR 3)",
+        "((15) NOR ((16)))",
+        "0 AND (10 OR (11 XOR (12 NOR 13))) XOR 0",
+    ] {
+        let tree = LogicTree::from_str(source).unwrap();
+        let reparsed = LogicTree::from_str(&tree.to_string()).unwrap();
+        assert_eq!(reparsed.to_string(), tree.to_string());
+    }
+}
+
+#[test]
+fn s_expression_round_trip() {
+    let tree = LogicTree::parse_any("(and 0\n  (or 1 (nand 2 3)))").unwrap();
+    assert_eq!(tree.to_string(), "0 AND (1 OR (2 NAND 3))");
+    assert_eq!(tree.to_s_expression(), "(and 0 (or 1 (nand 2 3)))");
+
+    let tree = LogicTree::from_str("0 AND 1 OR ((0 NAND 2) XOR 3)").unwrap();
+    let written = tree.to_s_expression();
+    assert_eq!(written, "(or (and 0 1) (xor (nand 0 2) 3))");
+    assert_eq!(Syntax::detect(&written), Syntax::SExpression);
+    let reparsed = LogicTree::with_syntax(&written, Syntax::SExpression).unwrap();
+    assert_eq!(reparsed.to_string(), tree.to_string());
+
+    assert_eq!(Syntax::detect(" ((0 AND 1))"), Syntax::Infix);
+    assert_eq!(Syntax::detect("42"), Syntax::Infix);
+    assert!(LogicTree::with_syntax("(and 0 1)", Syntax::Infix).is_err());
+    assert!(LogicTree::from_str("(and 0 1)").is_err());
+    assert_eq!(
+        LogicTree::parse_any("0 XOR 1").unwrap().to_string(),
+        "0 XOR 1"
+    );
+}
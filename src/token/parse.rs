@@ -1,6 +1,7 @@
 use super::scan::{ScanError, Scanner};
-use super::{LogicTree, Token};
+use super::{LogicTree, Syntax, Token};
 use crate::gate::Gate;
+use crate::TerminalId;
 use thiserror::Error;
 
 use std::iter::Peekable;
@@ -13,26 +14,113 @@ pub enum ParseError {
     InvalidTerminalPlacement,
     #[error("gates must come between terminals")]
     InvalidGatePlacement,
+    #[error("s-expression gates take exactly two operands")]
+    InvalidArity,
     #[error("{0}")]
     ScanError(#[from] ScanError),
 }
 
+/// Receives the nodes of a parsed expression, children before their parents.
+pub(crate) trait Builder {
+    type Node;
+
+    fn terminal(&mut self, id: TerminalId) -> Self::Node;
+
+    fn gate(&mut self, gate: Gate, left: Self::Node, right: Self::Node) -> Self::Node;
+}
+
+struct Boxed;
+
+impl Builder for Boxed {
+    type Node = LogicTree;
+
+    fn terminal(&mut self, id: TerminalId) -> LogicTree {
+        LogicTree::Terminal(id)
+    }
+
+    fn gate(&mut self, gate: Gate, left: LogicTree, right: LogicTree) -> LogicTree {
+        LogicTree::Gate {
+            gate,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+}
+
 pub fn parse(source: &str) -> Result<LogicTree, ParseError> {
-    let scanned = Scanner::scan(source)?;
-    parse_next(&mut scanned.iter().peekable())?.ok_or(ParseError::InvalidExpression)
+    build(&Scanner::scan(source)?, Syntax::Infix, &mut Boxed)
+}
+
+pub fn parse_with_syntax(source: &str, syntax: Syntax) -> Result<LogicTree, ParseError> {
+    parse_with(source, syntax, &mut Boxed)
+}
+
+pub(crate) fn parse_with<B: Builder>(
+    source: &str,
+    syntax: Syntax,
+    builder: &mut B,
+) -> Result<B::Node, ParseError> {
+    build(&Scanner::scan_with_syntax(source, syntax)?, syntax, builder)
+}
+
+fn build<B: Builder>(
+    scanned: &[Token],
+    syntax: Syntax,
+    builder: &mut B,
+) -> Result<B::Node, ParseError> {
+    let mut scanned = scanned.iter().peekable();
+    match syntax {
+        Syntax::Infix => parse_next(&mut scanned, builder)?.ok_or(ParseError::InvalidExpression),
+        Syntax::SExpression => {
+            let tree = parse_list(&mut scanned, builder)?;
+            match scanned.next() {
+                None => Ok(tree),
+                Some(Token::Gate(_)) => Err(ParseError::InvalidGatePlacement),
+                Some(_) => Err(ParseError::InvalidTerminalPlacement),
+            }
+        }
+    }
+}
+
+// every list is a gate followed by exactly two operands, so no lookahead is
+// needed
+fn parse_list<B: Builder>(
+    scanned: &mut Peekable<std::slice::Iter<'_, Token>>,
+    builder: &mut B,
+) -> Result<B::Node, ParseError> {
+    match scanned.next() {
+        None => Err(ParseError::InvalidExpression),
+        Some(Token::Whitespace) => unreachable!("use with pre-scanned input"),
+        Some(Token::Terminal(c)) => Ok(builder.terminal(*c)),
+        Some(Token::Gate(_)) => Err(ParseError::InvalidGatePlacement),
+        Some(Token::ClosingParenthesis) => Err(ParseError::InvalidArity),
+        Some(Token::OpeningParenthesis) => {
+            let gate = match scanned.next() {
+                Some(Token::Gate(gate)) => *gate,
+                _ => return Err(ParseError::InvalidGatePlacement),
+            };
+            let left = parse_list(scanned, builder)?;
+            let right = parse_list(scanned, builder)?;
+            match scanned.next() {
+                Some(Token::ClosingParenthesis) => Ok(builder.gate(gate, left, right)),
+                _ => Err(ParseError::InvalidArity),
+            }
+        }
+    }
 }
 
-fn parse_next(
+fn parse_next<B: Builder>(
     scanned: &mut Peekable<std::slice::Iter<'_, Token>>,
-) -> Result<Option<LogicTree>, ParseError> {
-    let mut current_tree: Option<LogicTree> = None;
+    builder: &mut B,
+) -> Result<Option<B::Node>, ParseError> {
+    let mut current_tree: Option<B::Node> = None;
     let mut current_gate: Option<Gate> = None;
     while let Some(token) = scanned.next() {
         (current_tree, current_gate) = match token {
             Token::Whitespace => unreachable!("use with pre-scanned input"),
             Token::OpeningParenthesis => {
-                if let Some(new_leaf) = parse_next(scanned)? {
-                    try_finalize_leaf(current_tree, current_gate, new_leaf)?
+                if let Some(new_leaf) = parse_next(scanned, builder)? {
+                    try_finalize_leaf(current_tree, current_gate, new_leaf, builder)?
                 } else {
                     (current_tree, current_gate)
                 }
@@ -46,7 +134,8 @@ fn parse_next(
                 return result;
             }
             Token::Terminal(c) => {
-                try_finalize_leaf(current_tree, current_gate, LogicTree::Terminal(*c))?
+                let new_leaf = builder.terminal(*c);
+                try_finalize_leaf(current_tree, current_gate, new_leaf, builder)?
             }
             Token::Gate(gate) => {
                 if current_tree.is_some() && current_gate.is_none() {
@@ -65,23 +154,19 @@ fn parse_next(
     }
 }
 
-fn try_finalize_leaf(
-    current_tree: Option<LogicTree>,
+type Partial<N> = (Option<N>, Option<Gate>);
+
+fn try_finalize_leaf<B: Builder>(
+    current_tree: Option<B::Node>,
     current_gate: Option<Gate>,
-    new_leaf: LogicTree,
-) -> Result<(Option<LogicTree>, Option<Gate>), ParseError> {
+    new_leaf: B::Node,
+    builder: &mut B,
+) -> Result<Partial<B::Node>, ParseError> {
     match (current_tree, current_gate) {
         (None, None) => Ok((Some(new_leaf), None)),
         (None, Some(_)) => Err(ParseError::InvalidGatePlacement),
         (Some(_), None) => Err(ParseError::InvalidTerminalPlacement),
-        (Some(tree), Some(gate)) => Ok((
-            Some(LogicTree::Gate {
-                gate,
-                left: Box::new(tree),
-                right: Box::new(new_leaf),
-            }),
-            None,
-        )),
+        (Some(tree), Some(gate)) => Ok((Some(builder.gate(gate, tree, new_leaf)), None)),
     }
 }
 
@@ -178,6 +263,39 @@ mod test {
         ));
     }
 
+    #[test]
+    fn parse_s_expression() {
+        let parsed = parse_with_syntax("(xor (and 0 (or 10 11)) 0)", Syntax::SExpression).unwrap();
+        let expected = parse("0 AND (10 OR 11) XOR 0").unwrap();
+        assert_eq!(parsed.to_string(), expected.to_string());
+
+        match parse_with_syntax("  7 ", Syntax::SExpression).unwrap() {
+            LogicTree::Terminal(7) => {}
+            _ => panic!("should be terminal"),
+        }
+
+        for (source, error) in [
+            ("(and 0)", ParseError::InvalidArity),
+            ("(or 0 1 2)", ParseError::InvalidArity),
+            ("(0 1)", ParseError::InvalidGatePlacement),
+            ("(and (0) 1)", ParseError::InvalidGatePlacement),
+            ("(and nor 1)", ParseError::InvalidGatePlacement),
+            ("(and 0 1) 2", ParseError::InvalidTerminalPlacement),
+            ("(and 0 1) or", ParseError::InvalidGatePlacement),
+        ] {
+            assert!(
+                is_equal_discriminant(
+                    &parse_with_syntax(source, Syntax::SExpression)
+                        .err()
+                        .unwrap(),
+                    &error
+                ),
+                "{}",
+                source
+            );
+        }
+    }
+
     #[test]
     fn parse_valid_statement() {
         // empty parentheses are "discarded"
@@ -1,4 +1,4 @@
-use super::Token;
+use super::{Syntax, Token};
 use crate::gate::Gate;
 use crate::TerminalId;
 use thiserror::Error;
@@ -23,6 +23,7 @@ pub enum ScanError {
 pub struct Scanner<'a> {
     source: Peekable<Chars<'a>>,
     lexeme: String,
+    syntax: Syntax,
 }
 
 impl<'a, 'b> Scanner<'a>
@@ -30,9 +31,14 @@ where
     'b: 'a,
 {
     pub fn scan(source: &'b str) -> Result<Vec<Token>, ScanError> {
+        Self::scan_with_syntax(source, Syntax::Infix)
+    }
+
+    pub fn scan_with_syntax(source: &'b str, syntax: Syntax) -> Result<Vec<Token>, ScanError> {
         let mut scanner = Self {
             source: source.chars().peekable(),
             lexeme: String::new(),
+            syntax,
         };
 
         let mut tokens = Vec::new();
@@ -68,7 +74,11 @@ where
         let next_char = self.advance().ok_or(ScanError::EofReached)?;
 
         match next_char {
-            c if matches!(c, ' ') => Ok(Token::Whitespace),
+            ' ' => Ok(Token::Whitespace),
+            // S-expressions are commonly spread over several lines
+            c if self.syntax == Syntax::SExpression && c.is_ascii_whitespace() => {
+                Ok(Token::Whitespace)
+            }
             '(' => Ok(Token::OpeningParenthesis),
             ')' => Ok(Token::ClosingParenthesis),
             c if c.is_ascii_digit() => {
@@ -77,19 +87,23 @@ where
                     TerminalId::from_str_radix(&self.lexeme, 10).map_err(|e| anyhow::anyhow!(e))?;
                 Ok(Token::Terminal(terminal_id))
             }
-            c if c.is_ascii_uppercase() => {
+            c if self.syntax == Syntax::Infix && c.is_ascii_uppercase() => {
                 self.advance_while(char::is_ascii_uppercase);
                 let boolean_gate = Gate::from_str(&self.lexeme)?;
                 Ok(Token::Gate(boolean_gate))
             }
+            c if self.syntax == Syntax::SExpression && c.is_ascii_lowercase() => {
+                self.advance_while(char::is_ascii_lowercase);
+                let boolean_gate = Gate::from_str(&self.lexeme.to_ascii_uppercase())?;
+                Ok(Token::Gate(boolean_gate))
+            }
             _ => Err(ScanError::InvalidToken(next_char)),
         }
     }
 
     fn advance(&mut self) -> Option<char> {
-        self.source.next().map(|c| {
+        self.source.next().inspect(|&c| {
             self.lexeme.push(c);
-            c
         })
     }
 
@@ -220,6 +234,28 @@ mod test {
         assert!(Scanner::scan("Or").is_err());
     }
 
+    #[test]
+    fn scan_s_expression() {
+        assert_eq!(
+            &Scanner::scan_with_syntax("(and 0\n\t(nor 12 3))", Syntax::SExpression).unwrap(),
+            &[
+                Token::OpeningParenthesis,
+                Token::Gate(Gate::And),
+                Token::Terminal(0),
+                Token::OpeningParenthesis,
+                Token::Gate(Gate::Nor),
+                Token::Terminal(12),
+                Token::Terminal(3),
+                Token::ClosingParenthesis,
+                Token::ClosingParenthesis,
+            ]
+        );
+        assert!(Scanner::scan_with_syntax("(AND 0 1)", Syntax::SExpression).is_err());
+        assert!(Scanner::scan_with_syntax("(ad 0 1)", Syntax::SExpression).is_err());
+        assert!(Scanner::scan("0 and 1").is_err());
+        assert!(Scanner::scan("0\nAND 1").is_err());
+    }
+
     #[test]
     fn scan_expression() {
         assert_eq!(
@@ -0,0 +1,237 @@
+use crate::gate::Gate;
+use crate::token::LogicTree;
+use crate::TerminalId;
+
+/// Read-only traversal of a [`LogicTree`].
+///
+/// Every method has a default implementation that descends into the children,
+/// so implementors only override the nodes they are interested in. When
+/// overriding a method that should keep descending, call the matching `walk_*`
+/// function.
+pub trait Visitor {
+    fn visit_tree(&mut self, tree: &LogicTree) {
+        walk_tree(self, tree)
+    }
+
+    fn visit_terminal(&mut self, _id: TerminalId) {}
+
+    fn visit_gate(&mut self, _gate: Gate, left: &LogicTree, right: &LogicTree) {
+        walk_gate(self, left, right)
+    }
+}
+
+pub fn walk_tree<V: Visitor + ?Sized>(visitor: &mut V, tree: &LogicTree) {
+    match tree {
+        LogicTree::Terminal(id) => visitor.visit_terminal(*id),
+        LogicTree::Gate { gate, left, right } => visitor.visit_gate(*gate, left, right),
+    }
+}
+
+pub fn walk_gate<V: Visitor + ?Sized>(visitor: &mut V, left: &LogicTree, right: &LogicTree) {
+    visitor.visit_tree(left);
+    visitor.visit_tree(right);
+}
+
+/// Mutable traversal of a [`LogicTree`].
+///
+/// Overriding [`VisitorMut::visit_tree_mut`] allows replacing whole subtrees,
+/// the other methods edit nodes in place.
+pub trait VisitorMut {
+    fn visit_tree_mut(&mut self, tree: &mut LogicTree) {
+        walk_tree_mut(self, tree)
+    }
+
+    fn visit_terminal_mut(&mut self, _id: &mut TerminalId) {}
+
+    fn visit_gate_mut(&mut self, _gate: &mut Gate, left: &mut LogicTree, right: &mut LogicTree) {
+        walk_gate_mut(self, left, right)
+    }
+}
+
+pub fn walk_tree_mut<V: VisitorMut + ?Sized>(visitor: &mut V, tree: &mut LogicTree) {
+    match tree {
+        LogicTree::Terminal(id) => visitor.visit_terminal_mut(id),
+        LogicTree::Gate { gate, left, right } => visitor.visit_gate_mut(gate, left, right),
+    }
+}
+
+pub fn walk_gate_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    left: &mut LogicTree,
+    right: &mut LogicTree,
+) {
+    visitor.visit_tree_mut(left);
+    visitor.visit_tree_mut(right);
+}
+
+/// Bottom-up reduction of a [`LogicTree`] into a single value.
+///
+/// Children are folded first (left, then right) and their outputs are handed
+/// to [`Fold::fold_gate`].
+pub trait Fold {
+    type Output;
+
+    fn fold_terminal(&mut self, id: TerminalId) -> Self::Output;
+
+    fn fold_gate(&mut self, gate: Gate, left: Self::Output, right: Self::Output) -> Self::Output;
+
+    fn fold_tree(&mut self, tree: &LogicTree) -> Self::Output {
+        match tree {
+            LogicTree::Terminal(id) => self.fold_terminal(*id),
+            LogicTree::Gate { gate, left, right } => {
+                let left = self.fold_tree(left);
+                let right = self.fold_tree(right);
+                self.fold_gate(*gate, left, right)
+            }
+        }
+    }
+}
+
+/// Iterator over the nodes of a [`LogicTree`], parents before children.
+pub struct Preorder<'a> {
+    stack: Vec<&'a LogicTree>,
+}
+
+impl<'a> Iterator for Preorder<'a> {
+    type Item = &'a LogicTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if let LogicTree::Gate { left, right, .. } = node {
+            self.stack.push(right);
+            self.stack.push(left);
+        }
+        Some(node)
+    }
+}
+
+/// Iterator over the nodes of a [`LogicTree`], children before parents.
+pub struct Postorder<'a> {
+    // the flag marks whether the children of a node were already pushed
+    stack: Vec<(&'a LogicTree, bool)>,
+}
+
+impl<'a> Iterator for Postorder<'a> {
+    type Item = &'a LogicTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            match node {
+                LogicTree::Gate { left, right, .. } if !expanded => {
+                    self.stack.push((node, true));
+                    self.stack.push((right, false));
+                    self.stack.push((left, false));
+                }
+                _ => return Some(node),
+            }
+        }
+    }
+}
+
+impl LogicTree {
+    pub fn accept<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_tree(self)
+    }
+
+    pub fn accept_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_tree_mut(self)
+    }
+
+    pub fn fold<F: Fold + ?Sized>(&self, folder: &mut F) -> F::Output {
+        folder.fold_tree(self)
+    }
+
+    pub fn preorder(&self) -> Preorder<'_> {
+        Preorder { stack: vec![self] }
+    }
+
+    pub fn postorder(&self) -> Postorder<'_> {
+        Postorder {
+            stack: vec![(self, false)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    fn label(node: &LogicTree) -> String {
+        match node {
+            LogicTree::Terminal(id) => id.to_string(),
+            LogicTree::Gate { gate, .. } => gate.to_string(),
+        }
+    }
+
+    #[test]
+    fn traversal_order() {
+        let tree = LogicTree::from_str("0 AND (1 OR 2) XOR 3").unwrap();
+        let preorder = tree.preorder().map(label).collect::<Vec<_>>();
+        assert_eq!(preorder, ["XOR", "AND", "0", "OR", "1", "2", "3"]);
+        let postorder = tree.postorder().map(label).collect::<Vec<_>>();
+        assert_eq!(postorder, ["0", "1", "2", "OR", "AND", "3", "XOR"]);
+
+        let tree = LogicTree::from_str("5").unwrap();
+        assert_eq!(tree.preorder().count(), 1);
+        assert_eq!(tree.postorder().count(), 1);
+    }
+
+    #[test]
+    fn visitor() {
+        struct GateCounter(usize);
+        impl Visitor for GateCounter {
+            fn visit_gate(&mut self, _gate: Gate, left: &LogicTree, right: &LogicTree) {
+                self.0 += 1;
+                walk_gate(self, left, right);
+            }
+        }
+
+        let tree = LogicTree::from_str("0 AND (1 OR 2) XOR 3").unwrap();
+        let mut counter = GateCounter(0);
+        tree.accept(&mut counter);
+        assert_eq!(counter.0, 3);
+    }
+
+    #[test]
+    fn visitor_mut() {
+        struct Relabel(HashMap<TerminalId, TerminalId>);
+        impl VisitorMut for Relabel {
+            fn visit_terminal_mut(&mut self, id: &mut TerminalId) {
+                if let Some(new_id) = self.0.get(id) {
+                    *id = *new_id;
+                }
+            }
+        }
+
+        let mut tree = LogicTree::from_str("0 AND (1 OR 2)").unwrap();
+        tree.accept_mut(&mut Relabel([(0, 10), (2, 20)].into_iter().collect()));
+        let ids = tree
+            .preorder()
+            .filter_map(|node| match node {
+                LogicTree::Terminal(id) => Some(*id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [10, 1, 20]);
+    }
+
+    #[test]
+    fn fold() {
+        struct Printer;
+        impl Fold for Printer {
+            type Output = String;
+            fn fold_terminal(&mut self, id: TerminalId) -> String {
+                id.to_string()
+            }
+            fn fold_gate(&mut self, gate: Gate, left: String, right: String) -> String {
+                format!("({} {} {})", left, gate, right)
+            }
+        }
+
+        let tree = LogicTree::from_str("0 AND (1 OR 2) XOR 3").unwrap();
+        assert_eq!(tree.fold(&mut Printer), "((0 AND (1 OR 2)) XOR 3)");
+    }
+}